@@ -0,0 +1,141 @@
+//! Assertion built-in functions
+//!
+//! Failed checks return `JingError::AssertionFailed`, which `jing test`
+//! reports as a test failure rather than an error.
+
+use crate::convert::optional_arg;
+use crate::error::{JingError, JingResult};
+use crate::features::{Arity, BuiltinFunction};
+use crate::value::Value;
+
+/// Fail unless a condition is truthy
+#[derive(Debug)]
+pub struct AssertFunction;
+
+impl BuiltinFunction for AssertFunction {
+    fn name(&self) -> &str {
+        "assert"
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::range(1, 2)
+    }
+
+    fn call(&self, args: Vec<Value>) -> JingResult<Value> {
+        let message: Option<String> = optional_arg(self.name(), &args, 1)?;
+        if args[0].is_truthy() {
+            Ok(Value::Nil)
+        } else {
+            Err(JingError::assertion_failed(message.unwrap_or_else(|| {
+                format!("expected a truthy value, got {}", args[0].repr())
+            })))
+        }
+    }
+
+    fn help(&self) -> &str {
+        "assert(condition, message?) - Fail with message unless condition is truthy"
+    }
+}
+
+/// Fail unless two values are equal
+#[derive(Debug)]
+pub struct AssertEqFunction;
+
+impl BuiltinFunction for AssertEqFunction {
+    fn name(&self) -> &str {
+        "assert_eq"
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::range(2, 3)
+    }
+
+    fn call(&self, args: Vec<Value>) -> JingResult<Value> {
+        let message: Option<String> = optional_arg(self.name(), &args, 2)?;
+        let (left, right) = (&args[0], &args[1]);
+        if left.equals(right) {
+            return Ok(Value::Nil);
+        }
+
+        let mut text = message.unwrap_or_else(|| "values are not equal".to_string());
+        text.push_str(&format!(
+            "\n   left: {}\n  right: {}",
+            left.repr(),
+            right.repr()
+        ));
+        if let Some(difference) = difference(left, right) {
+            text.push_str(&format!("\n   diff: {}", difference));
+        }
+        Err(JingError::assertion_failed(text))
+    }
+
+    fn help(&self) -> &str {
+        "assert_eq(left, right, message?) - Fail, showing both values, unless they are equal"
+    }
+}
+
+/// Fail if two values are equal
+#[derive(Debug)]
+pub struct AssertNeFunction;
+
+impl BuiltinFunction for AssertNeFunction {
+    fn name(&self) -> &str {
+        "assert_ne"
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::range(2, 3)
+    }
+
+    fn call(&self, args: Vec<Value>) -> JingResult<Value> {
+        let message: Option<String> = optional_arg(self.name(), &args, 2)?;
+        if !args[0].equals(&args[1]) {
+            return Ok(Value::Nil);
+        }
+
+        let text = message.unwrap_or_else(|| "values are equal".to_string());
+        Err(JingError::assertion_failed(format!(
+            "{}\n  value: {}",
+            text,
+            args[0].repr()
+        )))
+    }
+
+    fn help(&self) -> &str {
+        "assert_ne(left, right, message?) - Fail if the two values are equal"
+    }
+}
+
+/// Describe where two unequal lists or maps first differ
+///
+/// Returns `None` for scalars, where showing both values says it all.
+fn difference(left: &Value, right: &Value) -> Option<String> {
+    match (left, right) {
+        (Value::List(a), Value::List(b)) => match a.iter().zip(b).position(|(x, y)| !x.equals(y)) {
+            Some(index) => Some(nested(&format!("[{}]", index), &a[index], &b[index])),
+            None => Some(format!("lengths differ: {} vs {}", a.len(), b.len())),
+        },
+        (Value::Map(a), Value::Map(b)) => {
+            a.keys()
+                .chain(b.keys())
+                .find_map(|key| match (a.get(key), b.get(key)) {
+                    (Some(x), Some(y)) if x.equals(y) => None,
+                    (Some(x), Some(y)) => Some(nested(&format!("[{:?}]", key), x, y)),
+                    (Some(_), None) => Some(format!("key {:?} only in left", key)),
+                    (None, _) => Some(format!("key {:?} only in right", key)),
+                })
+        }
+        _ => None,
+    }
+}
+
+/// Difference at `path`, descending into nested collections
+fn nested(path: &str, left: &Value, right: &Value) -> String {
+    match difference(left, right) {
+        Some(inner) => match inner.strip_prefix("at ") {
+            Some(rest) => format!("at {}{}", path, rest),
+            None => format!("at {}: {}", path, inner),
+        },
+        None => format!("at {}: {} != {}", path, left.repr(), right.repr()),
+    }
+}
@@ -1,10 +1,10 @@
 //! Core built-in functions
 
 use crate::error::JingResult;
-use crate::features::BuiltinFunction;
+use crate::features::{Arity, BuiltinFunction};
 use crate::value::Value;
 
-/// Print function - displays values to stdout
+/// Print function - displays any number of values to stdout
 #[derive(Debug)]
 pub struct PrintFunction;
 
@@ -13,24 +13,18 @@ impl BuiltinFunction for PrintFunction {
         "print"
     }
 
-    fn arity(&self) -> usize {
-        1
+    fn arity(&self) -> Arity {
+        Arity::at_least(0)
     }
 
     fn call(&self, args: Vec<Value>) -> JingResult<Value> {
-        debug_assert_eq!(
-            args.len(),
-            1,
-            "print() takes exactly 1 argument, got {}",
-            args.len()
-        );
-
-        println!("{}", args[0]);
+        let parts: Vec<String> = args.iter().map(|value| value.to_string()).collect();
+        println!("{}", parts.join(" "));
         Ok(Value::Nil)
     }
 
     fn help(&self) -> &str {
-        "print(value) - Print a value to standard output"
+        "print(values...) - Print values separated by spaces to standard output"
     }
 }
 
@@ -43,8 +37,8 @@ impl BuiltinFunction for TypeFunction {
         "type"
     }
 
-    fn arity(&self) -> usize {
-        1
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
     }
 
     fn call(&self, args: Vec<Value>) -> JingResult<Value> {
@@ -55,16 +49,7 @@ impl BuiltinFunction for TypeFunction {
             args.len()
         );
 
-        let type_name = match &args[0] {
-            Value::Number(_) => "number",
-            Value::String(_) => "string",
-            Value::Bool(_) => "bool",
-            Value::Nil => "nil",
-            Value::Function { .. } => "function",
-            Value::BuiltinFunction { .. } => "builtin_function",
-        };
-
-        Ok(Value::String(type_name.to_string()))
+        Ok(Value::String(args[0].type_name().to_string()))
     }
 
     fn help(&self) -> &str {
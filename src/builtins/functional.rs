@@ -0,0 +1,176 @@
+//! Higher-order built-in functions
+//!
+//! These builtins take Jing functions as arguments and call back into
+//! the VM through the `CallContext`, so they cannot be called without one.
+
+use crate::convert::arg;
+use crate::error::{JingError, JingResult};
+use crate::features::{Arity, BuiltinFunction, CallContext};
+use crate::value::Value;
+use std::cmp::Ordering;
+
+/// Error returned when a higher-order builtin is called outside a VM
+fn requires_vm(name: &str) -> JingError {
+    JingError::runtime_error(format!("{}() can only be called from a running VM", name))
+}
+
+/// Check that `value` can be called, naming the argument in the error
+fn callable_arg(function: &str, args: &[Value], index: usize) -> JingResult<Value> {
+    match arg::<Value>(function, args, index)? {
+        callable @ (Value::Function { .. } | Value::BuiltinFunction { .. }) => Ok(callable),
+        other => Err(JingError::type_error(format!(
+            "{}() argument {} expected function, got {}",
+            function,
+            index + 1,
+            other.type_name()
+        ))),
+    }
+}
+
+/// Call a function with a list of arguments
+#[derive(Debug)]
+pub struct ApplyFunction;
+
+impl BuiltinFunction for ApplyFunction {
+    fn name(&self) -> &str {
+        "apply"
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(2)
+    }
+
+    fn call(&self, _args: Vec<Value>) -> JingResult<Value> {
+        Err(requires_vm(self.name()))
+    }
+
+    fn call_with_context(&self, args: Vec<Value>, context: &mut CallContext) -> JingResult<Value> {
+        let function = callable_arg(self.name(), &args, 0)?;
+        let call_args: Vec<Value> = arg(self.name(), &args, 1)?;
+        context.call(&function, call_args)
+    }
+
+    fn help(&self) -> &str {
+        "apply(function, args) - Call a function with the values in a list as arguments"
+    }
+}
+
+/// Transform each element of a list
+#[derive(Debug)]
+pub struct MapFunction;
+
+impl BuiltinFunction for MapFunction {
+    fn name(&self) -> &str {
+        "map"
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(2)
+    }
+
+    fn call(&self, _args: Vec<Value>) -> JingResult<Value> {
+        Err(requires_vm(self.name()))
+    }
+
+    fn call_with_context(&self, args: Vec<Value>, context: &mut CallContext) -> JingResult<Value> {
+        let items: Vec<Value> = arg(self.name(), &args, 0)?;
+        let function = callable_arg(self.name(), &args, 1)?;
+
+        let mapped = items
+            .into_iter()
+            .map(|item| context.call(&function, vec![item]))
+            .collect::<JingResult<Vec<Value>>>()?;
+        Ok(Value::List(mapped))
+    }
+
+    fn help(&self) -> &str {
+        "map(list, function) - Return a list with function applied to each element"
+    }
+}
+
+/// Keep the elements of a list for which a predicate is truthy
+#[derive(Debug)]
+pub struct FilterFunction;
+
+impl BuiltinFunction for FilterFunction {
+    fn name(&self) -> &str {
+        "filter"
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(2)
+    }
+
+    fn call(&self, _args: Vec<Value>) -> JingResult<Value> {
+        Err(requires_vm(self.name()))
+    }
+
+    fn call_with_context(&self, args: Vec<Value>, context: &mut CallContext) -> JingResult<Value> {
+        let items: Vec<Value> = arg(self.name(), &args, 0)?;
+        let predicate = callable_arg(self.name(), &args, 1)?;
+
+        let mut kept = Vec::new();
+        for item in items {
+            if context.call(&predicate, vec![item.clone()])?.is_truthy() {
+                kept.push(item);
+            }
+        }
+        Ok(Value::List(kept))
+    }
+
+    fn help(&self) -> &str {
+        "filter(list, predicate) - Return the elements for which predicate returns a truthy value"
+    }
+}
+
+/// Sort a list by a key computed for each element
+#[derive(Debug)]
+pub struct SortByFunction;
+
+impl BuiltinFunction for SortByFunction {
+    fn name(&self) -> &str {
+        "sort_by"
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(2)
+    }
+
+    fn call(&self, _args: Vec<Value>) -> JingResult<Value> {
+        Err(requires_vm(self.name()))
+    }
+
+    fn call_with_context(&self, args: Vec<Value>, context: &mut CallContext) -> JingResult<Value> {
+        let items: Vec<Value> = arg(self.name(), &args, 0)?;
+        let key_function = callable_arg(self.name(), &args, 1)?;
+
+        let mut keyed = Vec::with_capacity(items.len());
+        for item in items {
+            let key = context.call(&key_function, vec![item.clone()])?;
+            keyed.push((key, item));
+        }
+
+        // Keys must all be numbers or all be strings to have a total order
+        let comparable = keyed.iter().all(|(key, _)| matches!(key, Value::Number(_)))
+            || keyed.iter().all(|(key, _)| matches!(key, Value::String(_)));
+        if !comparable {
+            return Err(JingError::type_error(
+                "sort_by() keys must be all numbers or all strings",
+            ));
+        }
+
+        keyed.sort_by(|(a, _), (b, _)| match (a, b) {
+            (Value::Number(x), Value::Number(y)) => x.partial_cmp(y).unwrap_or(Ordering::Equal),
+            (Value::String(x), Value::String(y)) => x.cmp(y),
+            _ => Ordering::Equal,
+        });
+
+        Ok(Value::List(
+            keyed.into_iter().map(|(_, item)| item).collect(),
+        ))
+    }
+
+    fn help(&self) -> &str {
+        "sort_by(list, key) - Return the list sorted by the key function's results"
+    }
+}
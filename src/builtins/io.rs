@@ -1,11 +1,12 @@
 //! I/O built-in functions
 
+use crate::convert::arg;
 use crate::error::{JingError, JingResult};
-use crate::features::BuiltinFunction;
+use crate::features::{Arity, BuiltinFunction};
+use crate::sandbox::Capabilities;
 use crate::value::Value;
 use std::fs;
 use std::io::{self, Write};
-use std::path::Path;
 
 /// Read a line from standard input
 #[derive(Debug)]
@@ -16,14 +17,23 @@ impl BuiltinFunction for ReadLineFunction {
         "readline"
     }
 
-    fn arity(&self) -> usize {
-        0
+    fn arity(&self) -> Arity {
+        Arity::exact(0)
     }
 
     fn call(&self, args: Vec<Value>) -> JingResult<Value> {
+        self.call_with_capabilities(args, &Capabilities::all())
+    }
+
+    fn call_with_capabilities(
+        &self,
+        args: Vec<Value>,
+        capabilities: &Capabilities,
+    ) -> JingResult<Value> {
         if !args.is_empty() {
             return Err(JingError::runtime_error("readline() takes no arguments"));
         }
+        capabilities.check_stdin()?;
 
         let mut input = String::new();
         match io::stdin().read_line(&mut input) {
@@ -58,39 +68,42 @@ impl BuiltinFunction for InputFunction {
         "input"
     }
 
-    fn arity(&self) -> usize {
-        1
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
     }
 
     fn call(&self, args: Vec<Value>) -> JingResult<Value> {
-        match &args[0] {
-            Value::String(prompt) => {
-                print!("{}", prompt);
-                io::stdout().flush().map_err(|e| {
-                    JingError::runtime_error(format!("Failed to flush output: {}", e))
-                })?;
-
-                let mut input = String::new();
-                match io::stdin().read_line(&mut input) {
-                    Ok(_) => {
-                        // Remove trailing newline
-                        if input.ends_with('\n') {
-                            input.pop();
-                            if input.ends_with('\r') {
-                                input.pop();
-                            }
-                        }
-                        Ok(Value::String(input))
+        self.call_with_capabilities(args, &Capabilities::all())
+    }
+
+    fn call_with_capabilities(
+        &self,
+        args: Vec<Value>,
+        capabilities: &Capabilities,
+    ) -> JingResult<Value> {
+        let prompt: String = arg(self.name(), &args, 0)?;
+        capabilities.check_stdin()?;
+        print!("{}", prompt);
+        io::stdout()
+            .flush()
+            .map_err(|e| JingError::runtime_error(format!("Failed to flush output: {}", e)))?;
+
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(_) => {
+                // Remove trailing newline
+                if input.ends_with('\n') {
+                    input.pop();
+                    if input.ends_with('\r') {
+                        input.pop();
                     }
-                    Err(e) => Err(JingError::runtime_error(format!(
-                        "Failed to read input: {}",
-                        e
-                    ))),
                 }
+                Ok(Value::String(input))
             }
-            _ => Err(JingError::TypeError {
-                message: "input() expects a string prompt".to_string(),
-            }),
+            Err(e) => Err(JingError::runtime_error(format!(
+                "Failed to read input: {}",
+                e
+            ))),
         }
     }
 
@@ -108,22 +121,27 @@ impl BuiltinFunction for ReadFileFunction {
         "read_file"
     }
 
-    fn arity(&self) -> usize {
-        1
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
     }
 
     fn call(&self, args: Vec<Value>) -> JingResult<Value> {
-        match &args[0] {
-            Value::String(file_path) => match fs::read_to_string(file_path) {
-                Ok(contents) => Ok(Value::String(contents)),
-                Err(e) => Err(JingError::runtime_error(format!(
-                    "Failed to read file '{}': {}",
-                    file_path, e
-                ))),
-            },
-            _ => Err(JingError::TypeError {
-                message: "read_file() expects a string file path".to_string(),
-            }),
+        self.call_with_capabilities(args, &Capabilities::all())
+    }
+
+    fn call_with_capabilities(
+        &self,
+        args: Vec<Value>,
+        capabilities: &Capabilities,
+    ) -> JingResult<Value> {
+        let file_path: String = arg(self.name(), &args, 0)?;
+        let path = capabilities.check_read(&file_path)?;
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(Value::String(contents)),
+            Err(e) => Err(JingError::runtime_error(format!(
+                "Failed to read file '{}': {}",
+                file_path, e
+            ))),
         }
     }
 
@@ -141,24 +159,28 @@ impl BuiltinFunction for WriteFileFunction {
         "write_file"
     }
 
-    fn arity(&self) -> usize {
-        2
+    fn arity(&self) -> Arity {
+        Arity::exact(2)
     }
 
     fn call(&self, args: Vec<Value>) -> JingResult<Value> {
-        match (&args[0], &args[1]) {
-            (Value::String(file_path), Value::String(content)) => {
-                match fs::write(file_path, content) {
-                    Ok(_) => Ok(Value::Nil),
-                    Err(e) => Err(JingError::runtime_error(format!(
-                        "Failed to write to file '{}': {}",
-                        file_path, e
-                    ))),
-                }
-            }
-            _ => Err(JingError::TypeError {
-                message: "write_file() expects (file_path: string, content: string)".to_string(),
-            }),
+        self.call_with_capabilities(args, &Capabilities::all())
+    }
+
+    fn call_with_capabilities(
+        &self,
+        args: Vec<Value>,
+        capabilities: &Capabilities,
+    ) -> JingResult<Value> {
+        let file_path: String = arg(self.name(), &args, 0)?;
+        let content: String = arg(self.name(), &args, 1)?;
+        let path = capabilities.check_write(&file_path)?;
+        match fs::write(path, content) {
+            Ok(_) => Ok(Value::Nil),
+            Err(e) => Err(JingError::runtime_error(format!(
+                "Failed to write to file '{}': {}",
+                file_path, e
+            ))),
         }
     }
 
@@ -176,20 +198,22 @@ impl BuiltinFunction for FileExistsFunction {
         "file_exists"
     }
 
-    fn arity(&self) -> usize {
-        1
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
     }
 
     fn call(&self, args: Vec<Value>) -> JingResult<Value> {
-        match &args[0] {
-            Value::String(file_path) => {
-                let path = Path::new(file_path);
-                Ok(Value::Bool(path.exists()))
-            }
-            _ => Err(JingError::TypeError {
-                message: "file_exists() expects a string file path".to_string(),
-            }),
-        }
+        self.call_with_capabilities(args, &Capabilities::all())
+    }
+
+    fn call_with_capabilities(
+        &self,
+        args: Vec<Value>,
+        capabilities: &Capabilities,
+    ) -> JingResult<Value> {
+        let file_path: String = arg(self.name(), &args, 0)?;
+        let path = capabilities.check_read(&file_path)?;
+        Ok(Value::Bool(path.exists()))
     }
 
     fn help(&self) -> &str {
@@ -0,0 +1,84 @@
+//! List built-in functions
+
+use crate::convert::arg;
+use crate::error::{JingError, JingResult};
+use crate::features::{Arity, BuiltinFunction, CallContext};
+use crate::value::Value;
+
+/// Build a list of consecutive numbers
+#[derive(Debug)]
+pub struct RangeFunction;
+
+impl BuiltinFunction for RangeFunction {
+    fn name(&self) -> &str {
+        "range"
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
+    }
+
+    fn call(&self, args: Vec<Value>) -> JingResult<Value> {
+        let count: i64 = arg(self.name(), &args, 0)?;
+        Ok(Value::List(
+            (0..count).map(|i| Value::Number(i as f64)).collect(),
+        ))
+    }
+
+    fn call_with_context(&self, args: Vec<Value>, context: &mut CallContext) -> JingResult<Value> {
+        // Refuse lists over the memory limit before building them
+        let count: i64 = arg(self.name(), &args, 0)?;
+        let count = usize::try_from(count).unwrap_or(0);
+        context.check_allocation(count.saturating_mul(std::mem::size_of::<Value>()))?;
+        self.call(args)
+    }
+
+    fn help(&self) -> &str {
+        "range(n) - Return the list [0, 1, ..., n - 1]"
+    }
+}
+
+/// Get an element of a list or map
+#[derive(Debug)]
+pub struct GetFunction;
+
+impl BuiltinFunction for GetFunction {
+    fn name(&self) -> &str {
+        "get"
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(2)
+    }
+
+    fn call(&self, args: Vec<Value>) -> JingResult<Value> {
+        match &args[0] {
+            Value::List(items) => {
+                let index: i64 = arg(self.name(), &args, 1)?;
+                usize::try_from(index)
+                    .ok()
+                    .and_then(|index| items.get(index))
+                    .cloned()
+                    .ok_or_else(|| {
+                        JingError::runtime_error(format!(
+                            "get() index {} out of bounds for list of length {}",
+                            index,
+                            items.len()
+                        ))
+                    })
+            }
+            Value::Map(entries) => {
+                let key: String = arg(self.name(), &args, 1)?;
+                Ok(entries.get(&key).cloned().unwrap_or(Value::Nil))
+            }
+            other => Err(JingError::type_error(format!(
+                "get() argument 1 expected list or map, got {}",
+                other.type_name()
+            ))),
+        }
+    }
+
+    fn help(&self) -> &str {
+        "get(collection, key) - Return a list element by index or a map value by key (nil if missing)"
+    }
+}
@@ -1,9 +1,18 @@
 //! Mathematical built-in functions
 
+use crate::convert::{arg, optional_arg};
 use crate::error::{JingError, JingResult};
-use crate::features::BuiltinFunction;
+use crate::features::{Arity, BuiltinFunction};
 use crate::value::Value;
 
+/// Convert every argument of `function` to a number
+fn numbers(function: &str, args: &[Value]) -> JingResult<impl Iterator<Item = f64>> {
+    let ns = (0..args.len())
+        .map(|i| arg::<f64>(function, args, i))
+        .collect::<JingResult<Vec<f64>>>()?;
+    Ok(ns.into_iter())
+}
+
 /// Square root function
 #[derive(Debug)]
 pub struct SqrtFunction;
@@ -13,24 +22,18 @@ impl BuiltinFunction for SqrtFunction {
         "sqrt"
     }
 
-    fn arity(&self) -> usize {
-        1
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
     }
 
     fn call(&self, args: Vec<Value>) -> JingResult<Value> {
-        match &args[0] {
-            Value::Number(n) => {
-                if *n < 0.0 {
-                    Err(JingError::runtime_error(
-                        "Cannot take square root of negative number",
-                    ))
-                } else {
-                    Ok(Value::Number(n.sqrt()))
-                }
-            }
-            _ => Err(JingError::TypeError {
-                message: "sqrt() expects a number".to_string(),
-            }),
+        let n: f64 = arg(self.name(), &args, 0)?;
+        if n < 0.0 {
+            Err(JingError::runtime_error(
+                "Cannot take square root of negative number",
+            ))
+        } else {
+            Ok(Value::Number(n.sqrt()))
         }
     }
 
@@ -48,17 +51,13 @@ impl BuiltinFunction for AbsFunction {
         "abs"
     }
 
-    fn arity(&self) -> usize {
-        1
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
     }
 
     fn call(&self, args: Vec<Value>) -> JingResult<Value> {
-        match &args[0] {
-            Value::Number(n) => Ok(Value::Number(n.abs())),
-            _ => Err(JingError::TypeError {
-                message: "abs() expects a number".to_string(),
-            }),
-        }
+        let n: f64 = arg(self.name(), &args, 0)?;
+        Ok(Value::Number(n.abs()))
     }
 
     fn help(&self) -> &str {
@@ -66,7 +65,7 @@ impl BuiltinFunction for AbsFunction {
     }
 }
 
-/// Maximum of two numbers
+/// Maximum of one or more numbers
 #[derive(Debug)]
 pub struct MaxFunction;
 
@@ -75,25 +74,20 @@ impl BuiltinFunction for MaxFunction {
         "max"
     }
 
-    fn arity(&self) -> usize {
-        2
+    fn arity(&self) -> Arity {
+        Arity::at_least(1)
     }
 
     fn call(&self, args: Vec<Value>) -> JingResult<Value> {
-        match (&args[0], &args[1]) {
-            (Value::Number(a), Value::Number(b)) => Ok(Value::Number(a.max(*b))),
-            _ => Err(JingError::TypeError {
-                message: "max() expects two numbers".to_string(),
-            }),
-        }
+        numbers(self.name(), &args).map(|ns| Value::Number(ns.fold(f64::NEG_INFINITY, f64::max)))
     }
 
     fn help(&self) -> &str {
-        "max(a, b) - Return the maximum of two numbers"
+        "max(a, b, ...) - Return the maximum of one or more numbers"
     }
 }
 
-/// Minimum of two numbers  
+/// Minimum of one or more numbers
 #[derive(Debug)]
 pub struct MinFunction;
 
@@ -102,20 +96,45 @@ impl BuiltinFunction for MinFunction {
         "min"
     }
 
-    fn arity(&self) -> usize {
-        2
+    fn arity(&self) -> Arity {
+        Arity::at_least(1)
+    }
+
+    fn call(&self, args: Vec<Value>) -> JingResult<Value> {
+        numbers(self.name(), &args).map(|ns| Value::Number(ns.fold(f64::INFINITY, f64::min)))
+    }
+
+    fn help(&self) -> &str {
+        "min(a, b, ...) - Return the minimum of one or more numbers"
+    }
+}
+
+/// Round a number, optionally to a number of decimal places
+#[derive(Debug)]
+pub struct RoundFunction;
+
+impl BuiltinFunction for RoundFunction {
+    fn name(&self) -> &str {
+        "round"
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::range(1, 2)
     }
 
     fn call(&self, args: Vec<Value>) -> JingResult<Value> {
-        match (&args[0], &args[1]) {
-            (Value::Number(a), Value::Number(b)) => Ok(Value::Number(a.min(*b))),
-            _ => Err(JingError::TypeError {
-                message: "min() expects two numbers".to_string(),
-            }),
+        let n: f64 = arg(self.name(), &args, 0)?;
+        let digits: Option<i64> = optional_arg(self.name(), &args, 1)?;
+        match digits {
+            None | Some(0) => Ok(Value::Number(n.round())),
+            Some(digits) => {
+                let scale = 10f64.powi(digits.clamp(-308, 308) as i32);
+                Ok(Value::Number((n * scale).round() / scale))
+            }
         }
     }
 
     fn help(&self) -> &str {
-        "min(a, b) - Return the minimum of two numbers"
+        "round(number, digits?) - Round a number to the nearest integer or to digits decimal places"
     }
 }
@@ -2,72 +2,66 @@
 //!
 //! This module contains implementations of built-in functions using
 //! the modular trait system. Adding new builtins is as simple as
-//! implementing the BuiltinFunction trait and registering it.
+//! implementing the BuiltinFunction trait and registering it in
+//! `register_defaults` (or on a single VM's `Registry`).
 
+pub mod assert;
 pub mod core;
+pub mod functional;
 pub mod io;
+pub mod list;
 pub mod math;
+pub mod native;
 pub mod string;
+pub mod system;
 
-use crate::registry::register_builtin;
+use crate::registry::Registry;
 use std::sync::Arc;
 
-/// Initialize all built-in functions
-pub fn init_builtins() {
+/// Register all standard built-in functions into `registry`
+pub fn register_defaults(registry: &mut Registry) {
     // Core functions
-    register_builtin(Arc::new(core::PrintFunction));
-    register_builtin(Arc::new(core::TypeFunction));
+    registry.register(Arc::new(core::PrintFunction));
+    registry.register(Arc::new(core::TypeFunction));
 
     // Math functions
-    register_builtin(Arc::new(math::SqrtFunction));
-    register_builtin(Arc::new(math::AbsFunction));
-    register_builtin(Arc::new(math::MaxFunction));
-    register_builtin(Arc::new(math::MinFunction));
+    registry.register(Arc::new(math::SqrtFunction));
+    registry.register(Arc::new(math::AbsFunction));
+    registry.register(Arc::new(math::MaxFunction));
+    registry.register(Arc::new(math::MinFunction));
+    registry.register(Arc::new(math::RoundFunction));
 
     // String functions
-    register_builtin(Arc::new(string::LenFunction));
-    register_builtin(Arc::new(string::UpperFunction));
-    register_builtin(Arc::new(string::LowerFunction));
-    register_builtin(Arc::new(string::ReverseFunction));
+    registry.register(Arc::new(string::LenFunction));
+    registry.register(Arc::new(string::UpperFunction));
+    registry.register(Arc::new(string::LowerFunction));
+    registry.register(Arc::new(string::ReverseFunction));
 
-    // I/O functions
-    register_builtin(Arc::new(io::ReadLineFunction));
-    register_builtin(Arc::new(io::InputFunction));
-    register_builtin(Arc::new(io::ReadFileFunction));
-    register_builtin(Arc::new(io::WriteFileFunction));
-    register_builtin(Arc::new(io::FileExistsFunction));
-}
+    // List functions
+    registry.register(Arc::new(list::RangeFunction));
+    registry.register(Arc::new(list::GetFunction));
 
-/// Get all registered builtin function names
-pub fn get_builtin_names() -> Vec<String> {
-    crate::registry::builtin_names()
-}
+    // Higher-order functions
+    registry.register(Arc::new(functional::ApplyFunction));
+    registry.register(Arc::new(functional::MapFunction));
+    registry.register(Arc::new(functional::FilterFunction));
+    registry.register(Arc::new(functional::SortByFunction));
 
-/// Check if a function name is a builtin
-pub fn is_builtin(name: &str) -> bool {
-    crate::registry::get_builtin(name).is_some()
-}
+    // I/O functions
+    registry.register(Arc::new(io::ReadLineFunction));
+    registry.register(Arc::new(io::InputFunction));
+    registry.register(Arc::new(io::ReadFileFunction));
+    registry.register(Arc::new(io::WriteFileFunction));
+    registry.register(Arc::new(io::FileExistsFunction));
+
+    // Assertions
+    registry.register(Arc::new(assert::AssertFunction));
+    registry.register(Arc::new(assert::AssertEqFunction));
+    registry.register(Arc::new(assert::AssertNeFunction));
 
-/// Call a builtin function
-pub fn call_builtin(
-    name: &str,
-    args: Vec<crate::value::Value>,
-) -> crate::error::JingResult<crate::value::Value> {
-    match crate::registry::get_builtin(name) {
-        Some(builtin) => {
-            if args.len() != builtin.arity() {
-                return Err(crate::error::JingError::runtime_error(format!(
-                    "Function '{}' expects {} arguments, got {}",
-                    name,
-                    builtin.arity(),
-                    args.len()
-                )));
-            }
-            builtin.call(args)
-        }
-        None => Err(crate::error::JingError::runtime_error(format!(
-            "Unknown builtin function: {}",
-            name
-        ))),
-    }
+    // System functions
+    registry.register(Arc::new(system::ArgsFunction));
+    registry.register(Arc::new(system::EnvFunction));
+    registry.register(Arc::new(system::SetEnvFunction));
+    registry.register(Arc::new(system::ExitFunction));
 }
@@ -0,0 +1,132 @@
+//! Builtins backed by Rust closures
+//!
+//! `NativeFunction` adapts a typed closure such as `|a: f64, b: f64| a + b`
+//! into a `BuiltinFunction`, converting arguments with `FromValue` and the
+//! result with `IntoValue`. Use `Registry::register_fn` or `VM::register_fn`
+//! instead of writing a struct and trait impl by hand.
+
+use crate::convert::{convert_arg, FromValue, IntoNativeResult};
+use crate::error::{JingError, JingResult};
+use crate::features::{Arity, BuiltinFunction};
+use crate::value::Value;
+use std::fmt;
+
+type NativeCallback = Box<dyn Fn(Vec<Value>) -> JingResult<Value> + Send + Sync>;
+
+/// A builtin function implemented by a Rust closure
+pub struct NativeFunction {
+    name: String,
+    arity: usize,
+    help: String,
+    callback: NativeCallback,
+}
+
+impl NativeFunction {
+    /// Wrap a typed closure as a builtin named `name`
+    pub fn new<Args, F: IntoNativeFunction<Args>>(name: impl Into<String>, function: F) -> Self {
+        let name = name.into();
+        let arity = F::arity();
+        let params: Vec<String> = (1..=arity).map(|i| format!("arg{}", i)).collect();
+        let help = format!("{}({}) - Native function", name, params.join(", "));
+        let callback = function.into_callback(name.clone());
+
+        NativeFunction {
+            name,
+            arity,
+            help,
+            callback,
+        }
+    }
+
+    /// Replace the generated help text
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = help.into();
+        self
+    }
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NativeFunction")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .finish()
+    }
+}
+
+impl BuiltinFunction for NativeFunction {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(self.arity)
+    }
+
+    fn call(&self, args: Vec<Value>) -> JingResult<Value> {
+        if args.len() != self.arity {
+            return Err(JingError::runtime_error(format!(
+                "{}() takes exactly {} arguments, got {}",
+                self.name,
+                self.arity,
+                args.len()
+            )));
+        }
+
+        (self.callback)(args)
+    }
+
+    fn help(&self) -> &str {
+        &self.help
+    }
+}
+
+/// Closures that can be turned into native builtins
+///
+/// Implemented for `Fn` closures of up to six arguments whose parameters
+/// implement `FromValue` and whose return type is a value or a `JingResult`.
+pub trait IntoNativeFunction<Args> {
+    /// Number of arguments the closure takes
+    fn arity() -> usize;
+
+    /// Erase the closure's types into a callback over `Value`s
+    fn into_callback(self, name: String) -> NativeCallback;
+}
+
+macro_rules! impl_into_native_function {
+    ($count:expr $(, $arg:ident : $index:expr)*) => {
+        impl<F, R, $($arg,)*> IntoNativeFunction<($($arg,)*)> for F
+        where
+            F: Fn($($arg),*) -> R + Send + Sync + 'static,
+            R: IntoNativeResult,
+            $($arg: FromValue,)*
+        {
+            fn arity() -> usize {
+                $count
+            }
+
+            #[allow(unused_mut, unused_variables, non_snake_case)]
+            fn into_callback(self, name: String) -> NativeCallback {
+                Box::new(move |args: Vec<Value>| {
+                    let mut args = args.into_iter();
+                    $(
+                        let $arg: $arg = convert_arg(
+                            &name,
+                            $index,
+                            args.next().unwrap_or(Value::Nil),
+                        )?;
+                    )*
+                    (self)($($arg),*).into_native_result()
+                })
+            }
+        }
+    };
+}
+
+impl_into_native_function!(0);
+impl_into_native_function!(1, A: 0);
+impl_into_native_function!(2, A: 0, B: 1);
+impl_into_native_function!(3, A: 0, B: 1, C: 2);
+impl_into_native_function!(4, A: 0, B: 1, C: 2, D: 3);
+impl_into_native_function!(5, A: 0, B: 1, C: 2, D: 3, E: 4);
+impl_into_native_function!(6, A: 0, B: 1, C: 2, D: 3, E: 4, G: 5);
@@ -1,7 +1,8 @@
 //! String manipulation built-in functions
 
+use crate::convert::arg;
 use crate::error::{JingError, JingResult};
-use crate::features::BuiltinFunction;
+use crate::features::{Arity, BuiltinFunction};
 use crate::value::Value;
 
 /// String length function
@@ -13,21 +14,24 @@ impl BuiltinFunction for LenFunction {
         "len"
     }
 
-    fn arity(&self) -> usize {
-        1
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
     }
 
     fn call(&self, args: Vec<Value>) -> JingResult<Value> {
         match &args[0] {
             Value::String(s) => Ok(Value::Number(s.len() as f64)),
-            _ => Err(JingError::TypeError {
-                message: "len() expects a string".to_string(),
-            }),
+            Value::List(items) => Ok(Value::Number(items.len() as f64)),
+            Value::Map(entries) => Ok(Value::Number(entries.len() as f64)),
+            other => Err(JingError::type_error(format!(
+                "len() argument 1 expected string, list or map, got {}",
+                other.type_name()
+            ))),
         }
     }
 
     fn help(&self) -> &str {
-        "len(string) - Return the length of a string"
+        "len(value) - Return the length of a string, list or map"
     }
 }
 
@@ -40,17 +44,13 @@ impl BuiltinFunction for UpperFunction {
         "upper"
     }
 
-    fn arity(&self) -> usize {
-        1
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
     }
 
     fn call(&self, args: Vec<Value>) -> JingResult<Value> {
-        match &args[0] {
-            Value::String(s) => Ok(Value::String(s.to_uppercase())),
-            _ => Err(JingError::TypeError {
-                message: "upper() expects a string".to_string(),
-            }),
-        }
+        let s: String = arg(self.name(), &args, 0)?;
+        Ok(Value::String(s.to_uppercase()))
     }
 
     fn help(&self) -> &str {
@@ -67,17 +67,13 @@ impl BuiltinFunction for LowerFunction {
         "lower"
     }
 
-    fn arity(&self) -> usize {
-        1
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
     }
 
     fn call(&self, args: Vec<Value>) -> JingResult<Value> {
-        match &args[0] {
-            Value::String(s) => Ok(Value::String(s.to_lowercase())),
-            _ => Err(JingError::TypeError {
-                message: "lower() expects a string".to_string(),
-            }),
-        }
+        let s: String = arg(self.name(), &args, 0)?;
+        Ok(Value::String(s.to_lowercase()))
     }
 
     fn help(&self) -> &str {
@@ -94,8 +90,8 @@ impl BuiltinFunction for ReverseFunction {
         "reverse"
     }
 
-    fn arity(&self) -> usize {
-        1
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
     }
 
     fn call(&self, args: Vec<Value>) -> JingResult<Value> {
@@ -105,15 +101,9 @@ impl BuiltinFunction for ReverseFunction {
             ));
         }
 
-        match &args[0] {
-            Value::String(s) => {
-                let reversed: String = s.chars().rev().collect();
-                Ok(Value::String(reversed))
-            }
-            _ => Err(JingError::runtime_error(
-                "reverse() argument must be a string",
-            )),
-        }
+        let s: String = arg(self.name(), &args, 0)?;
+        let reversed: String = s.chars().rev().collect();
+        Ok(Value::String(reversed))
     }
 
     fn help(&self) -> &str {
@@ -0,0 +1,154 @@
+//! System built-in functions
+//!
+//! Access to the script's command-line arguments, environment variables
+//! and process exit status, for scripts used as command-line tools.
+
+use crate::convert::{arg, optional_arg};
+use crate::error::{JingError, JingResult};
+use crate::features::{Arity, BuiltinFunction, CallContext};
+use crate::sandbox::Capabilities;
+use crate::value::Value;
+use std::env;
+
+/// Get the command-line arguments passed to the script
+#[derive(Debug)]
+pub struct ArgsFunction;
+
+impl BuiltinFunction for ArgsFunction {
+    fn name(&self) -> &str {
+        "args"
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(0)
+    }
+
+    fn call(&self, _args: Vec<Value>) -> JingResult<Value> {
+        Err(JingError::runtime_error(
+            "args() can only be called from a running VM",
+        ))
+    }
+
+    fn call_with_context(&self, _args: Vec<Value>, context: &mut CallContext) -> JingResult<Value> {
+        Ok(Value::List(
+            context
+                .args()
+                .iter()
+                .map(|arg| Value::String(arg.clone()))
+                .collect(),
+        ))
+    }
+
+    fn help(&self) -> &str {
+        "args() - Return the command-line arguments after the script path as a list of strings"
+    }
+}
+
+/// Read an environment variable
+#[derive(Debug)]
+pub struct EnvFunction;
+
+impl BuiltinFunction for EnvFunction {
+    fn name(&self) -> &str {
+        "env"
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
+    }
+
+    fn call(&self, args: Vec<Value>) -> JingResult<Value> {
+        self.call_with_capabilities(args, &Capabilities::all())
+    }
+
+    fn call_with_capabilities(
+        &self,
+        args: Vec<Value>,
+        capabilities: &Capabilities,
+    ) -> JingResult<Value> {
+        let name: String = arg(self.name(), &args, 0)?;
+        capabilities.check_env()?;
+        Ok(env::var(&name).map_or(Value::Nil, Value::String))
+    }
+
+    fn call_with_context(&self, args: Vec<Value>, context: &mut CallContext) -> JingResult<Value> {
+        let name: String = arg(self.name(), &args, 0)?;
+        context.capabilities().check_env()?;
+        Ok(context.env_var(&name).map_or(Value::Nil, Value::String))
+    }
+
+    fn help(&self) -> &str {
+        "env(name) - Return the value of an environment variable, or nil if it is not set"
+    }
+}
+
+/// Set an environment variable for the rest of the script
+///
+/// The variable lives in the calling VM, which `env()` reads first; the
+/// process environment is not changed.
+#[derive(Debug)]
+pub struct SetEnvFunction;
+
+impl BuiltinFunction for SetEnvFunction {
+    fn name(&self) -> &str {
+        "set_env"
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(2)
+    }
+
+    fn call(&self, _args: Vec<Value>) -> JingResult<Value> {
+        Err(JingError::runtime_error(
+            "set_env() can only be called from a running VM",
+        ))
+    }
+
+    fn call_with_context(&self, args: Vec<Value>, context: &mut CallContext) -> JingResult<Value> {
+        let name: String = arg(self.name(), &args, 0)?;
+        let value: String = arg(self.name(), &args, 1)?;
+        context.capabilities().check_env()?;
+
+        if name.is_empty() || name.contains('=') || name.contains('\0') || value.contains('\0') {
+            return Err(JingError::runtime_error(format!(
+                "set_env() invalid variable name or value for '{}'",
+                name
+            )));
+        }
+        context.set_env_var(&name, &value);
+        Ok(Value::Nil)
+    }
+
+    fn help(&self) -> &str {
+        "set_env(name, value) - Set an environment variable for the rest of the script"
+    }
+}
+
+/// Stop the script with a process exit status
+#[derive(Debug)]
+pub struct ExitFunction;
+
+impl BuiltinFunction for ExitFunction {
+    fn name(&self) -> &str {
+        "exit"
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::range(0, 1)
+    }
+
+    fn call(&self, args: Vec<Value>) -> JingResult<Value> {
+        let code: Option<i64> = optional_arg(self.name(), &args, 0)?;
+        match code.unwrap_or(0) {
+            code @ 0..=255 => Err(JingError::exit(code as i32)),
+            code => Err(JingError::runtime_error(format!(
+                "exit() status must be between 0 and 255, got {}",
+                code
+            ))),
+        }
+    }
+
+    fn help(&self) -> &str {
+        "exit(code?) - Stop the script with the given exit status (default 0)"
+    }
+}
@@ -0,0 +1,712 @@
+//! Command-line interface of the `jing` binary
+//!
+//! Arguments are parsed by hand to keep the crate free of dependencies.
+//! Every command returns a process exit status: 0 on success, 64 for usage
+//! errors, 66 when an input file cannot be read, 1 when `jing test` has
+//! failures or `jing fmt --check` finds unformatted files, and otherwise the
+//! code from `JingError::exit_code`.
+
+use crate::compiler::{Chunk, Compiler};
+use crate::debugger::Debugger;
+use crate::error::{JingError, JingResult};
+use crate::formatter;
+use crate::lexer::Lexer;
+use crate::lsp;
+use crate::parser::Parser;
+use crate::repl::REPL;
+use crate::testing;
+use crate::vm::VM;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Read};
+use std::path::Path;
+use std::time::Instant;
+
+/// Exit status for invalid command-line usage (`EX_USAGE`)
+pub const EXIT_USAGE: i32 = 64;
+/// Exit status when an input file cannot be read (`EX_NOINPUT`)
+pub const EXIT_NO_INPUT: i32 = 66;
+/// Exit status of `jing test` when a test fails
+pub const EXIT_TEST_FAILURE: i32 = 1;
+/// Exit status of `jing fmt --check` when a file is not formatted
+pub const EXIT_UNFORMATTED: i32 = 1;
+
+/// Script path that reads the program from standard input
+const STDIN_PATH: &str = "-";
+
+/// Subcommands: name, usage and description
+const COMMANDS: &[(&str, &str, &str)] = &[
+    ("run", "run <file> [args]...", "Run a script"),
+    ("repl", "repl", "Start the interactive REPL (the default)"),
+    (
+        "check",
+        "check <file>...",
+        "Check scripts for errors without running them",
+    ),
+    (
+        "disasm",
+        "disasm <file>",
+        "Print the bytecode compiled from a script",
+    ),
+    (
+        "debug",
+        "debug <file> [args]...",
+        "Run a script in the step debugger",
+    ),
+    (
+        "fmt",
+        "fmt [--check] <file>...",
+        "Format source files in place (- formats stdin to stdout)",
+    ),
+    (
+        "test",
+        "test [path]... [--filter <name>]",
+        "Run test_* functions in *_test.jing files",
+    ),
+    (
+        "lsp",
+        "lsp",
+        "Start a language server speaking LSP on stdin/stdout",
+    ),
+    ("help", "help [command]", "Show help for jing or a command"),
+];
+
+/// Global options: usage and description
+const OPTIONS: &[(&str, &str)] = &[
+    ("--no-color", "Disable colored output"),
+    ("-q, --quiet", "Only print errors and program output"),
+    ("-e, --eval <code>", "Run code given on the command line"),
+    ("-h, --help", "Show this help"),
+    ("-V, --version", "Show the version"),
+];
+
+/// What the binary was asked to do
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Run {
+        path: String,
+        args: Vec<String>,
+    },
+    Eval {
+        source: String,
+        args: Vec<String>,
+    },
+    Repl,
+    Check {
+        paths: Vec<String>,
+    },
+    Disasm {
+        path: String,
+    },
+    Debug {
+        path: String,
+        args: Vec<String>,
+    },
+    Fmt {
+        paths: Vec<String>,
+        /// Report unformatted files instead of rewriting them
+        check: bool,
+    },
+    Test {
+        paths: Vec<String>,
+        filter: Option<String>,
+    },
+    Lsp,
+    Help {
+        command: Option<String>,
+    },
+    Version,
+}
+
+/// Flags that apply to every command
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    /// Allow colored error output (still only used on a terminal)
+    pub color: bool,
+    /// Suppress banners and status messages
+    pub quiet: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            color: true,
+            quiet: false,
+        }
+    }
+}
+
+/// A parsed command line
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cli {
+    pub command: Command,
+    pub options: Options,
+}
+
+impl Cli {
+    /// Parse the arguments that follow the program name
+    ///
+    /// A lone file name runs that file, `-` reads the program from standard
+    /// input, and no arguments start the REPL. Everything after the script
+    /// path (or after the code given to `-e`) is passed to the script unparsed.
+    pub fn parse(args: &[String]) -> Result<Cli, String> {
+        let mut options = Options::default();
+        let mut positional = Vec::new();
+        let mut script_args = Vec::new();
+        let mut help = false;
+        let mut filter = None;
+        let mut expect_filter = false;
+        let mut check = false;
+
+        for (index, arg) in args.iter().enumerate() {
+            if expect_filter {
+                filter = Some(arg.clone());
+                expect_filter = false;
+                continue;
+            }
+
+            match arg.as_str() {
+                "--no-color" => options.color = false,
+                "-q" | "--quiet" => options.quiet = true,
+                "-h" | "--help" => help = true,
+                "--filter" => expect_filter = true,
+                "--check" => check = true,
+                "-e" | "--eval" if positional.is_empty() => {
+                    let source = args
+                        .get(index + 1)
+                        .ok_or_else(|| format!("{} needs code to run", arg))?;
+                    return Ok(Cli {
+                        command: Command::Eval {
+                            source: source.clone(),
+                            args: args[index + 2..].to_vec(),
+                        },
+                        options,
+                    });
+                }
+                "-V" | "--version" => {
+                    return Ok(Cli {
+                        command: Command::Version,
+                        options,
+                    })
+                }
+                flag if flag.starts_with('-') && flag.len() > 1 => {
+                    return Err(format!("unknown option '{}'", flag));
+                }
+                _ => positional.push(arg.clone()),
+            }
+
+            if !help && is_script_path(&positional) {
+                script_args = args[index + 1..].to_vec();
+                break;
+            }
+        }
+
+        if expect_filter {
+            return Err("--filter needs a test name".to_string());
+        }
+
+        let mut positional = positional.into_iter();
+        let name = positional.next();
+        let rest: Vec<String> = positional.collect();
+
+        let command = match name.as_deref() {
+            // `jing -h file.jing` asks about running scripts, not a command
+            Some(path) if help && !is_command(path) && looks_like_script(path) => {
+                Command::Help { command: None }
+            }
+            _ if help => Command::Help { command: name },
+            None | Some("repl") => {
+                no_extra_args("repl", &rest)?;
+                Command::Repl
+            }
+            Some("help") => Command::Help {
+                command: rest.first().cloned(),
+            },
+            Some("run") => Command::Run {
+                path: single_path("run", rest)?,
+                args: script_args,
+            },
+            Some("check") => Command::Check {
+                paths: some_paths("check", rest)?,
+            },
+            Some("disasm") => Command::Disasm {
+                path: single_path("disasm", rest)?,
+            },
+            Some("debug") => match single_path("debug", rest)? {
+                path if path == STDIN_PATH => {
+                    return Err("debug reads commands from standard input; give a file".to_string())
+                }
+                path => Command::Debug {
+                    path,
+                    args: script_args,
+                },
+            },
+            Some("fmt") => Command::Fmt {
+                paths: some_paths("fmt", rest)?,
+                check: std::mem::take(&mut check),
+            },
+            Some("test") => Command::Test {
+                paths: rest,
+                filter: filter.take(),
+            },
+            Some("lsp") => {
+                no_extra_args("lsp", &rest)?;
+                Command::Lsp
+            }
+            Some(path) if looks_like_script(path) => Command::Run {
+                path: path.to_string(),
+                args: script_args,
+            },
+            Some(other) => return Err(format!("unknown command '{}'", other)),
+        };
+
+        if filter.is_some() {
+            return Err("--filter is only valid for 'jing test'".to_string());
+        }
+        if check {
+            return Err("--check is only valid for 'jing fmt'".to_string());
+        }
+
+        Ok(Cli { command, options })
+    }
+}
+
+/// Whether the positional arguments so far end with the script to run
+fn is_script_path(positional: &[String]) -> bool {
+    match positional {
+        [command, _] => command == "run" || command == "debug",
+        [path] => !is_command(path) && looks_like_script(path),
+        _ => false,
+    }
+}
+
+fn is_command(word: &str) -> bool {
+    COMMANDS.iter().any(|(name, _, _)| *name == word)
+}
+
+fn looks_like_script(path: &str) -> bool {
+    path == STDIN_PATH || path.ends_with(".jing") || Path::new(path).is_file()
+}
+
+fn no_extra_args(command: &str, rest: &[String]) -> Result<(), String> {
+    match rest.first() {
+        Some(arg) => Err(format!("unexpected argument '{}' for {}", arg, command)),
+        None => Ok(()),
+    }
+}
+
+fn single_path(command: &str, rest: Vec<String>) -> Result<String, String> {
+    let mut rest = rest.into_iter();
+    let path = rest
+        .next()
+        .ok_or_else(|| format!("{} needs a file", command))?;
+    no_extra_args(command, &rest.collect::<Vec<_>>())?;
+    Ok(path)
+}
+
+fn some_paths(command: &str, rest: Vec<String>) -> Result<Vec<String>, String> {
+    if rest.is_empty() {
+        Err(format!("{} needs at least one file", command))
+    } else {
+        Ok(rest)
+    }
+}
+
+/// General help text listing all commands
+pub fn usage() -> String {
+    let mut text = String::from(
+        "Jing programming language\n\n\
+         Usage: jing [OPTIONS] [COMMAND] [ARGS]\n       \
+         jing [OPTIONS] <file.jing | -> [ARGS]\n       \
+         jing [OPTIONS] -e <code> [ARGS]\n\nCommands:\n",
+    );
+    // One column width for both tables, so descriptions line up
+    let commands = COMMANDS
+        .iter()
+        .map(|(_, usage, description)| (*usage, *description));
+    let width = commands
+        .clone()
+        .chain(OPTIONS.iter().copied())
+        .map(|(usage, _)| usage.len())
+        .max()
+        .unwrap_or(0);
+    for (usage, description) in commands {
+        text.push_str(&format!("  {:<width$}  {}\n", usage, description));
+    }
+    text.push_str("\nOptions:\n");
+    for (usage, description) in OPTIONS {
+        text.push_str(&format!("  {:<width$}  {}\n", usage, description));
+    }
+    text
+}
+
+/// Help text for a single command
+pub fn command_usage(name: &str) -> Option<String> {
+    COMMANDS
+        .iter()
+        .find(|(command, _, _)| *command == name)
+        .map(|(_, usage, description)| format!("Usage: jing {}\n\n{}\n", usage, description))
+}
+
+/// Parse `args` (without the program name), run the command and return
+/// the process exit status
+pub fn main(args: &[String]) -> i32 {
+    match Cli::parse(args) {
+        Ok(cli) => cli.run(),
+        Err(message) => {
+            let reporter = Reporter::new(&Options::default());
+            reporter.error(&message);
+            eprintln!("Run 'jing --help' for usage.");
+            EXIT_USAGE
+        }
+    }
+}
+
+impl Cli {
+    /// Run the parsed command, returning the process exit status
+    pub fn run(&self) -> i32 {
+        let reporter = Reporter::new(&self.options);
+
+        match &self.command {
+            Command::Run { path, args } => match read_source(path) {
+                Ok(source) => {
+                    let script = (path != STDIN_PATH).then_some(path.as_str());
+                    reporter.result(run_source(&source, script, args))
+                }
+                Err(err) => reporter.fail(&err, EXIT_NO_INPUT),
+            },
+            Command::Eval { source, args } => reporter.result(run_source(source, None, args)),
+            Command::Repl => {
+                let mut repl = REPL::new();
+                repl.set_banner(!self.options.quiet);
+                reporter.result(repl.run())
+            }
+            Command::Check { paths } => {
+                let mut status = 0;
+                for path in paths {
+                    let result = read_source(path)
+                        .map_err(|err| (err, EXIT_NO_INPUT))
+                        .and_then(|source| {
+                            compile(&source).map_err(|err| {
+                                let code = err.exit_code();
+                                (err, code)
+                            })
+                        });
+                    match result {
+                        Ok(_) => reporter.info(&format!("{}: ok", path)),
+                        Err((err, code)) => {
+                            reporter.error(&format!("{}: {}", path, err));
+                            if status == 0 {
+                                status = code;
+                            }
+                        }
+                    }
+                }
+                status
+            }
+            Command::Disasm { path } => match read_source(path) {
+                Ok(source) => match compile(&source) {
+                    Ok(chunk) => {
+                        print!("{}", chunk.disassemble());
+                        0
+                    }
+                    Err(err) => reporter.fail(&err, err.exit_code()),
+                },
+                Err(err) => reporter.fail(&err, EXIT_NO_INPUT),
+            },
+            Command::Debug { path, args } => match read_source(path) {
+                Ok(source) => reporter.result(debug_source(&source, path, args)),
+                Err(err) => reporter.fail(&err, EXIT_NO_INPUT),
+            },
+            Command::Fmt { paths, check } => format_files(paths, *check, &reporter),
+            Command::Test { paths, filter } => run_tests(paths, filter.as_deref(), &reporter),
+            Command::Lsp => match lsp::Server::new().run(io::stdin().lock(), io::stdout().lock()) {
+                Ok(status) => status,
+                Err(err) => {
+                    let err = JingError::io_error(format!("Language server stopped: {}", err));
+                    reporter.fail(&err, err.exit_code())
+                }
+            },
+            Command::Help { command } => match command {
+                None => {
+                    print!("{}", usage());
+                    0
+                }
+                Some(name) => match command_usage(name) {
+                    Some(text) => {
+                        print!("{}", text);
+                        0
+                    }
+                    None => {
+                        reporter.error(&format!("unknown command '{}'", name));
+                        EXIT_USAGE
+                    }
+                },
+            },
+            Command::Version => {
+                println!("jing {}", env!("CARGO_PKG_VERSION"));
+                0
+            }
+        }
+    }
+}
+
+/// Run `jing fmt`, rewriting each file that is not formatted
+///
+/// With `check`, files are only listed and 1 is returned if any would
+/// change. The path `-` formats standard input to standard output.
+fn format_files(paths: &[String], check: bool, reporter: &Reporter) -> i32 {
+    let mut status = 0;
+    for path in paths {
+        let result = read_source(path)
+            .map_err(|err| (err, EXIT_NO_INPUT))
+            .and_then(|source| {
+                let formatted = formatter::format_source(&source).map_err(|err| {
+                    let code = err.exit_code();
+                    (err, code)
+                })?;
+                Ok((source, formatted))
+            });
+
+        let code = match result {
+            Err((err, code)) => {
+                reporter.error(&format!("{}: {}", path, err));
+                code
+            }
+            Ok((source, formatted)) if check => {
+                if formatted == source {
+                    0
+                } else {
+                    println!("{}: not formatted", path);
+                    EXIT_UNFORMATTED
+                }
+            }
+            Ok((_, formatted)) if path == STDIN_PATH => {
+                print!("{}", formatted);
+                0
+            }
+            Ok((source, formatted)) if formatted == source => 0,
+            Ok((_, formatted)) => match fs::write(path, formatted) {
+                Ok(()) => {
+                    reporter.info(&format!("Formatted {}", path));
+                    0
+                }
+                Err(err) => {
+                    let err =
+                        JingError::io_error(format!("Could not write file '{}': {}", path, err));
+                    reporter.fail(&err, err.exit_code())
+                }
+            },
+        };
+        if status == 0 {
+            status = code;
+        }
+    }
+    status
+}
+
+/// Run `jing test`, printing a line per test and a summary
+///
+/// Returns 0 when every test passes, 1 when any test fails or a test file
+/// does not compile, and 66 when a path does not exist.
+fn run_tests(paths: &[String], filter: Option<&str>, reporter: &Reporter) -> i32 {
+    let files = match testing::discover(paths) {
+        Ok(files) => files,
+        Err(err) => return reporter.fail(&err, EXIT_NO_INPUT),
+    };
+
+    let start = Instant::now();
+    let (mut passed, mut failed, mut filtered) = (0, 0, 0);
+    let mut failures = Vec::new();
+
+    for path in &files {
+        let report = testing::run_file(path, filter);
+        filtered += report.filtered;
+
+        if let Some(err) = report.error {
+            reporter.error(&format!("{}: {}", path.display(), err));
+            failed += 1;
+            continue;
+        }
+
+        for result in report.results {
+            let status = if result.passed() { "ok" } else { "FAILED" };
+            if !result.passed() || !reporter.quiet {
+                println!(
+                    "test {}::{} ... {} ({:.2?})",
+                    path.display(),
+                    result.name,
+                    status,
+                    result.duration
+                );
+            }
+            match result.outcome {
+                Ok(()) => passed += 1,
+                Err(err) => {
+                    failed += 1;
+                    failures.push((format!("{}::{}", path.display(), result.name), err));
+                }
+            }
+        }
+    }
+
+    for (name, err) in &failures {
+        println!("\n---- {} ----\n{}", name, err);
+    }
+
+    let verdict = if failed == 0 { "ok" } else { "FAILED" };
+    println!(
+        "\ntest result: {}. {} passed; {} failed; {} filtered out; finished in {:.2?}",
+        verdict,
+        passed,
+        failed,
+        filtered,
+        start.elapsed()
+    );
+
+    if failed == 0 {
+        0
+    } else {
+        EXIT_TEST_FAILURE
+    }
+}
+
+/// Read a script, reporting a missing or unreadable file as an I/O error
+///
+/// The path `-` reads the script from standard input.
+pub fn read_source(path: &str) -> JingResult<String> {
+    if path == STDIN_PATH {
+        let mut source = String::new();
+        return io::stdin()
+            .read_to_string(&mut source)
+            .map(|_| source)
+            .map_err(|err| JingError::io_error(format!("Could not read standard input: {}", err)));
+    }
+    fs::read_to_string(path)
+        .map_err(|err| JingError::io_error(format!("Could not read file '{}': {}", path, err)))
+}
+
+/// Lex, parse and compile source code without running it
+///
+/// The chunk records a line table, so `jing debug` can map its code back
+/// to the source.
+pub fn compile(source: &str) -> JingResult<Chunk> {
+    let tokens = Lexer::new(source).tokenize()?;
+    let mut parser = Parser::new(tokens);
+    let statements = parser.parse()?;
+    let mut compiler = Compiler::new();
+    compiler.set_lines(parser.statement_spans());
+    compiler.compile(statements)
+}
+
+/// Compile and run source code in a fresh VM, passing `args` to the script
+///
+/// `path` is the file the source was read from, used to resolve imports.
+pub fn run_source(source: &str, path: Option<&str>, args: &[String]) -> JingResult<()> {
+    let chunk = compile(source)?;
+    let mut vm = VM::new();
+    vm.set_args(args.to_vec());
+    if let Some(path) = path {
+        vm.set_script_path(path);
+    }
+    vm.interpret(chunk)
+}
+
+/// Run a script under the step debugger, reading commands from stdin
+///
+/// `quit` and the end of input stop the script with status 0.
+pub fn debug_source(source: &str, path: &str, args: &[String]) -> JingResult<()> {
+    let chunk = compile(source)?;
+    let mut vm = VM::new();
+    vm.set_args(args.to_vec());
+    vm.set_script_path(path);
+    let debugger = Debugger::new(source, StdinLines::default(), io::stdout());
+    vm.set_debug_hook(Some(Box::new(debugger)));
+    vm.interpret(chunk)
+}
+
+/// Standard input read one line at a time, without holding its lock
+///
+/// The debugger shares stdin with the script's `input()`, so it must leave
+/// stdin unlocked between commands and never read past the end of one.
+#[derive(Default)]
+struct StdinLines {
+    line: Vec<u8>,
+    consumed: usize,
+}
+
+impl Read for StdinLines {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let count = available.len().min(buf.len());
+        buf[..count].copy_from_slice(&available[..count]);
+        self.consume(count);
+        Ok(count)
+    }
+}
+
+impl BufRead for StdinLines {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.consumed == self.line.len() {
+            self.line.clear();
+            self.consumed = 0;
+            let mut stdin = io::stdin().lock();
+            let available = stdin.fill_buf()?;
+            let end = available
+                .iter()
+                .position(|&byte| byte == b'\n')
+                .map_or(available.len(), |newline| newline + 1);
+            self.line.extend_from_slice(&available[..end]);
+            stdin.consume(end);
+        }
+        Ok(&self.line[self.consumed..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.consumed = (self.consumed + amount).min(self.line.len());
+    }
+}
+
+/// Writes status and error messages to stderr, honouring the global flags
+struct Reporter {
+    color: bool,
+    quiet: bool,
+}
+
+impl Reporter {
+    fn new(options: &Options) -> Self {
+        let color =
+            options.color && std::env::var_os("NO_COLOR").is_none() && io::stderr().is_terminal();
+        Reporter {
+            color,
+            quiet: options.quiet,
+        }
+    }
+
+    fn error(&self, message: &str) {
+        if self.color {
+            eprintln!("\x1b[1;31merror:\x1b[0m {}", message);
+        } else {
+            eprintln!("error: {}", message);
+        }
+    }
+
+    fn info(&self, message: &str) {
+        if !self.quiet {
+            eprintln!("{}", message);
+        }
+    }
+
+    fn fail(&self, err: &JingError, code: i32) -> i32 {
+        self.error(&err.to_string());
+        code
+    }
+
+    fn result(&self, result: JingResult<()>) -> i32 {
+        match result {
+            Ok(()) => 0,
+            // `exit(code)` is a normal way to stop, not an error to report
+            Err(JingError::Exit { code }) => code,
+            Err(err) => self.fail(&err, err.exit_code()),
+        }
+    }
+}
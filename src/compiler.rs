@@ -1,4 +1,5 @@
 use crate::error::{JingError, JingResult};
+use crate::features::Arity;
 use crate::parser::*;
 use crate::value::Value;
 use std::collections::HashMap;
@@ -42,7 +43,19 @@ pub enum OpCode {
 
     // Function calls
     Call(usize), // arity
+    /// Call with positional arguments followed by named ones
+    CallNamed(usize, Vec<String>),
     Return,
+    /// Jump if the named parameter was bound by the caller
+    JumpIfBound(String, usize),
+    /// Pop top of stack into a local of the current call frame
+    BindLocal(String),
+
+    // Modules
+    /// Load a module (running it on first import) and push its exports
+    Import(String),
+    /// Replace the map on top of the stack with one of its fields
+    GetField(String),
 
     // Built-in functions
     Print,
@@ -57,25 +70,85 @@ pub struct Chunk {
     pub code: Vec<OpCode>,
     pub constants: Vec<Value>,
     pub functions: HashMap<String, FunctionInfo>,
+    /// Top-level names visible to scripts that import this chunk
+    pub exports: Vec<String>,
+    /// Line table: the address where each statement's code starts and the
+    /// source line of the statement, in address order
+    pub lines: Vec<(usize, usize)>,
+}
+
+/// How far a chunk had been compiled, to undo code appended after it
+///
+/// Function entries are kept whole, since appended code may redefine one.
+#[derive(Debug)]
+pub struct ChunkMark {
+    code: usize,
+    constants: usize,
+    exports: usize,
+    lines: usize,
+    functions: HashMap<String, FunctionInfo>,
 }
 
 #[derive(Debug, Clone)]
 pub struct FunctionInfo {
     pub name: String,
-    pub arity: usize,
+    /// Accepted argument counts; variadic when the last local is a rest parameter
+    pub arity: Arity,
     pub start_address: usize,
     pub locals: Vec<String>,
 }
 
+impl FunctionInfo {
+    /// Parameters that can be bound by position or name, excluding a
+    /// trailing rest parameter
+    pub fn fixed_params(&self) -> &[String] {
+        match self.arity.max {
+            None => self
+                .locals
+                .split_last()
+                .map_or(&[][..], |(_, params)| params),
+            Some(_) => &self.locals,
+        }
+    }
+}
+
+impl Default for Chunk {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Chunk {
     pub fn new() -> Self {
         Chunk {
             code: Vec::new(),
             constants: Vec::new(),
             functions: HashMap::new(),
+            exports: Vec::new(),
+            lines: Vec::new(),
         }
     }
 
+    /// Remember the current end of the chunk; see `rollback`
+    pub fn mark(&self) -> ChunkMark {
+        ChunkMark {
+            code: self.code.len(),
+            constants: self.constants.len(),
+            exports: self.exports.len(),
+            lines: self.lines.len(),
+            functions: self.functions.clone(),
+        }
+    }
+
+    /// Drop everything compiled since `mark` was taken
+    pub fn rollback(&mut self, mark: ChunkMark) {
+        self.code.truncate(mark.code);
+        self.constants.truncate(mark.constants);
+        self.exports.truncate(mark.exports);
+        self.lines.truncate(mark.lines);
+        self.functions = mark.functions;
+    }
+
     pub fn emit(&mut self, op: OpCode) {
         self.code.push(op);
     }
@@ -91,9 +164,78 @@ impl Chunk {
         self.code.len()
     }
 
+    /// Record that the statement on `line` starts at the next address
+    ///
+    /// A statement that emitted no code gives its address to the next one.
+    pub fn mark_line(&mut self, line: usize) {
+        let address = self.current_address();
+        match self.lines.last_mut() {
+            Some(last) if last.0 == address => last.1 = line,
+            _ => self.lines.push((address, line)),
+        }
+    }
+
+    /// Source line of the statement whose code contains `address`
+    pub fn line_at(&self, address: usize) -> Option<usize> {
+        let index = self.lines.partition_point(|(start, _)| *start <= address);
+        index.checked_sub(1).map(|index| self.lines[index].1)
+    }
+
+    /// Source line of the statement starting at `address`, if one does
+    pub fn line_starting_at(&self, address: usize) -> Option<usize> {
+        self.lines
+            .binary_search_by_key(&address, |(start, _)| *start)
+            .ok()
+            .map(|index| self.lines[index].1)
+    }
+
+    /// Render the bytecode as a human-readable listing
+    ///
+    /// Each line shows an address and instruction; constants are shown with
+    /// their values, and function entry points are labelled.
+    pub fn disassemble(&self) -> String {
+        let mut entries: Vec<&FunctionInfo> = self.functions.values().collect();
+        entries.sort_by_key(|info| info.start_address);
+
+        let mut out = String::new();
+        for (address, op) in self.code.iter().enumerate() {
+            for info in entries.iter().filter(|info| info.start_address == address) {
+                out.push_str(&format!(
+                    "<fn {}({})>:\n",
+                    info.name,
+                    info.locals.join(", ")
+                ));
+            }
+
+            let line = match op {
+                OpCode::Constant(index) => match self.constants.get(*index) {
+                    Some(value) => format!("Constant {} ; {}", index, value.repr()),
+                    None => format!("Constant {} ; <invalid>", index),
+                },
+                OpCode::Load(name) => format!("Load {}", name),
+                OpCode::Store(name) => format!("Store {}", name),
+                OpCode::Jump(target) => format!("Jump -> {:04}", target),
+                OpCode::JumpIfFalse(target) => format!("JumpIfFalse -> {:04}", target),
+                OpCode::JumpIfBound(name, target) => {
+                    format!("JumpIfBound {} -> {:04}", name, target)
+                }
+                OpCode::BindLocal(name) => format!("BindLocal {}", name),
+                OpCode::Import(path) => format!("Import {:?}", path),
+                OpCode::GetField(name) => format!("GetField {}", name),
+                OpCode::Call(arity) => format!("Call {}", arity),
+                OpCode::CallNamed(positional, names) => {
+                    format!("CallNamed {} ({})", positional, names.join(", "))
+                }
+                other => format!("{:?}", other),
+            };
+            out.push_str(&format!("{:04}  {}\n", address, line));
+        }
+        out
+    }
+
     pub fn patch_jump(&mut self, address: usize, target: usize) {
         match &mut self.code[address] {
-            OpCode::Jump(addr) | OpCode::JumpIfFalse(addr) => {
+            OpCode::Jump(addr) | OpCode::JumpIfFalse(addr) | OpCode::JumpIfBound(_, addr) => {
                 *addr = target;
             }
             _ => panic!("Cannot patch non-jump instruction"),
@@ -106,6 +248,9 @@ pub struct Compiler {
     chunk: Chunk,
     locals: Vec<String>,
     scope_depth: usize,
+    /// First line of each statement from `Parser::statement_spans`
+    lines: Vec<usize>,
+    next_line: usize,
 }
 
 impl Compiler {
@@ -114,6 +259,45 @@ impl Compiler {
             chunk: Chunk::new(),
             locals: Vec::new(),
             scope_depth: 0,
+            lines: Vec::new(),
+            next_line: 0,
+        }
+    }
+
+    /// Create a compiler that appends to previously compiled code
+    ///
+    /// Code compiled by `compile` starts at `chunk.code.len()` and can call
+    /// functions defined in `chunk`, since addresses in it stay valid.
+    pub fn with_chunk(chunk: Chunk) -> Self {
+        Compiler {
+            chunk,
+            locals: Vec::new(),
+            scope_depth: 0,
+            lines: Vec::new(),
+            next_line: 0,
+        }
+    }
+
+    /// The chunk being compiled, such as what was left after an error
+    pub fn into_chunk(self) -> Chunk {
+        self.chunk
+    }
+
+    /// Record a line table from the parser's statement spans
+    ///
+    /// The spans are listed in the order statements start, which is the
+    /// order the compiler visits the statements of the program and of its
+    /// blocks. Without spans the chunk gets no line table.
+    pub fn set_lines(&mut self, spans: &[LineSpan]) {
+        self.lines = spans.iter().map(|span| span.start).collect();
+        self.next_line = 0;
+    }
+
+    /// Mark where the next statement of a program or block starts
+    fn statement_line(&mut self) {
+        if let Some(&line) = self.lines.get(self.next_line) {
+            self.next_line += 1;
+            self.chunk.mark_line(line);
         }
     }
 
@@ -124,11 +308,13 @@ impl Compiler {
 
         // Compile all statements except the last
         for stmt in statements {
+            self.statement_line();
             self.compile_statement(stmt)?;
         }
 
         // Compile the last statement, but don't pop its result if it's an expression or block
         if let Some(stmt) = last_stmt {
+            self.statement_line();
             match stmt {
                 Stmt::Expression(expr_stmt) => {
                     self.compile_expression(expr_stmt.expr)?;
@@ -142,11 +328,13 @@ impl Compiler {
 
                     // Compile all statements except the last
                     for stmt in statements {
+                        self.statement_line();
                         self.compile_statement(stmt)?;
                     }
 
                     // Compile the last statement, but don't pop its result if it's an expression
                     if let Some(stmt) = last_stmt {
+                        self.statement_line();
                         match stmt {
                             Stmt::Expression(expr_stmt) => {
                                 self.compile_expression(expr_stmt.expr)?;
@@ -205,11 +393,13 @@ impl Compiler {
 
                 // Compile all statements except the last
                 for stmt in statements {
+                    self.statement_line();
                     self.compile_statement(stmt)?;
                 }
 
                 // Compile the last statement, but don't pop its result if it's an expression
                 if let Some(stmt) = last_stmt {
+                    self.statement_line();
                     match stmt {
                         Stmt::Expression(expr_stmt) => {
                             self.compile_expression(expr_stmt.expr)?;
@@ -244,6 +434,41 @@ impl Compiler {
                 }
                 self.chunk.emit(OpCode::Return);
             }
+            Stmt::Import(import_stmt) => {
+                self.require_top_level("import")?;
+                self.chunk.emit(OpCode::Import(import_stmt.path));
+                self.chunk.emit(OpCode::Store(import_stmt.alias.clone()));
+                if !self.locals.contains(&import_stmt.alias) {
+                    self.locals.push(import_stmt.alias);
+                }
+            }
+            Stmt::Export(export_stmt) => {
+                self.require_top_level("export")?;
+                let name = match export_stmt.declaration.as_ref() {
+                    Stmt::Let(let_stmt) => let_stmt.name.clone(),
+                    Stmt::Function(func_stmt) => func_stmt.name.clone(),
+                    _ => {
+                        return Err(JingError::compile_error(
+                            "Only let and fn declarations can be exported",
+                        ))
+                    }
+                };
+                self.compile_statement(*export_stmt.declaration)?;
+                if !self.chunk.exports.contains(&name) {
+                    self.chunk.exports.push(name);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reject `keyword` inside functions and blocks
+    fn require_top_level(&self, keyword: &str) -> JingResult<()> {
+        if self.scope_depth > 0 {
+            return Err(JingError::compile_error(format!(
+                "'{}' is only allowed at the top level of a file",
+                keyword
+            )));
         }
         Ok(())
     }
@@ -282,6 +507,10 @@ impl Compiler {
                 // Load the value back onto the stack (assignments are expressions)
                 self.chunk.emit(OpCode::Load(assign.name));
             }
+            Expr::Get(get) => {
+                self.compile_expression(*get.object)?;
+                self.chunk.emit(OpCode::GetField(get.name));
+            }
         }
         Ok(())
     }
@@ -352,15 +581,10 @@ impl Compiler {
     }
 
     fn compile_call_expression(&mut self, call: CallExpr) -> JingResult<()> {
-        // Handle print as a special case with its own opcode for now
-        // All other builtin functions go through the modular registry system
+        // Handle single-argument print as a special case with its own opcode
+        // All other calls (including variadic print) go through the registry
         if let Expr::Variable(var) = call.callee.as_ref() {
-            if var.name.as_str() == "print" {
-                if call.args.len() != 1 {
-                    return Err(JingError::compile_error(
-                        "print() expects exactly 1 argument",
-                    ));
-                }
+            if var.name.as_str() == "print" && call.args.len() == 1 && call.named.is_empty() {
                 self.compile_expression(call.args[0].clone())?;
                 self.chunk.emit(OpCode::Print);
                 return Ok(());
@@ -372,9 +596,69 @@ impl Compiler {
             self.compile_expression(arg.clone())?;
         }
 
-        // Compile function call - this will be handled by the VM's modular builtin system
+        if call.named.is_empty() {
+            // Compile function call - this will be handled by the VM's modular builtin system
+            self.compile_expression(*call.callee)?;
+            self.chunk.emit(OpCode::Call(call.args.len()));
+            return Ok(());
+        }
+
+        self.check_named_arguments(&call)?;
+
+        let names: Vec<String> = call.named.iter().map(|arg| arg.name.clone()).collect();
+        for arg in call.named {
+            self.compile_expression(arg.value)?;
+        }
+
         self.compile_expression(*call.callee)?;
-        self.chunk.emit(OpCode::Call(call.args.len()));
+        self.chunk.emit(OpCode::CallNamed(call.args.len(), names));
+        Ok(())
+    }
+
+    /// Reject duplicate named arguments, and unknown or conflicting ones
+    /// when the callee is a function declared earlier in the program
+    fn check_named_arguments(&self, call: &CallExpr) -> JingResult<()> {
+        for (i, arg) in call.named.iter().enumerate() {
+            if call.named[..i].iter().any(|other| other.name == arg.name) {
+                return Err(JingError::compile_error(format!(
+                    "Duplicate named argument '{}'",
+                    arg.name
+                )));
+            }
+        }
+
+        let func_info = match call.callee.as_ref() {
+            Expr::Variable(var) if !self.locals.contains(&var.name) => {
+                match self.chunk.functions.get(&var.name) {
+                    Some(func_info) => func_info,
+                    None => return Ok(()),
+                }
+            }
+            _ => return Ok(()),
+        };
+
+        for arg in &call.named {
+            match func_info
+                .fixed_params()
+                .iter()
+                .position(|param| *param == arg.name)
+            {
+                None => {
+                    return Err(JingError::compile_error(format!(
+                        "Function '{}' has no parameter named '{}'",
+                        func_info.name, arg.name
+                    )));
+                }
+                Some(index) if index < call.args.len() => {
+                    return Err(JingError::compile_error(format!(
+                        "Function '{}' got multiple values for argument '{}'",
+                        func_info.name, arg.name
+                    )));
+                }
+                Some(_) => {}
+            }
+        }
+
         Ok(())
     }
 
@@ -435,12 +719,27 @@ impl Compiler {
 
         let function_start = self.chunk.current_address();
 
+        let required = func_stmt
+            .params
+            .iter()
+            .filter(|param| param.default.is_none() && !param.rest)
+            .count();
+        let arity = if func_stmt.params.iter().any(|param| param.rest) {
+            Arity::at_least(required)
+        } else {
+            Arity::range(required, func_stmt.params.len())
+        };
+
         // Store function info
         let function_info = FunctionInfo {
             name: func_stmt.name.clone(),
-            arity: func_stmt.params.len(),
+            arity,
             start_address: function_start,
-            locals: func_stmt.params.clone(),
+            locals: func_stmt
+                .params
+                .iter()
+                .map(|param| param.name.clone())
+                .collect(),
         };
 
         self.chunk
@@ -452,7 +751,19 @@ impl Compiler {
 
         // Parameters are local variables
         for param in &func_stmt.params {
-            self.locals.push(param.clone());
+            self.locals.push(param.name.clone());
+        }
+
+        // Evaluate defaults for parameters the caller left out
+        for param in &func_stmt.params {
+            if let Some(default) = &param.default {
+                let bound_jump = self.chunk.current_address();
+                self.chunk.emit(OpCode::JumpIfBound(param.name.clone(), 0));
+                self.compile_expression(default.clone())?;
+                self.chunk.emit(OpCode::BindLocal(param.name.clone()));
+                let after_default = self.chunk.current_address();
+                self.chunk.patch_jump(bound_jump, after_default);
+            }
         }
 
         self.compile_statement(*func_stmt.body)?;
@@ -467,10 +778,12 @@ impl Compiler {
         self.chunk.patch_jump(skip_jump, function_end);
 
         // Define the function as a constant
+        // The module index is filled in when the chunk is loaded by the VM
         let func_value = Value::Function {
             name: func_stmt.name.clone(),
-            arity: func_stmt.params.len(),
+            arity,
             chunk_start: function_start,
+            module: 0,
         };
 
         self.chunk.emit_constant(func_value);
@@ -0,0 +1,306 @@
+//! Conversions between Jing `Value`s and Rust types.
+//!
+//! These traits let host code and native builtins work with plain Rust
+//! types instead of matching on `Value` variants by hand.
+
+use crate::error::{JingError, JingResult};
+use crate::value::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Convert a Jing value into a Rust type
+pub trait FromValue: Sized {
+    /// Convert `value`, failing with a `TypeError` if it has the wrong type
+    fn from_value(value: Value) -> JingResult<Self>;
+
+    /// Description of the accepted values, used in error messages
+    fn expected() -> String;
+}
+
+/// Convert a Rust type into a Jing value
+pub trait IntoValue {
+    fn into_value(self) -> Value;
+}
+
+/// Build the `TypeError` reported when a value has an unexpected type
+pub fn type_mismatch(expected: &str, value: &Value) -> JingError {
+    JingError::type_error(format!("expected {}, got {}", expected, value.type_name()))
+}
+
+/// Convert argument `index` (0-based) of builtin `function`, naming both in type errors
+pub fn convert_arg<T: FromValue>(function: &str, index: usize, value: Value) -> JingResult<T> {
+    T::from_value(value).map_err(|err| match err {
+        JingError::TypeError { message } => {
+            JingError::type_error(format!("{}() argument {} {}", function, index + 1, message))
+        }
+        other => other,
+    })
+}
+
+/// Convert `args[index]` of builtin `function`, failing if it was not passed
+pub fn arg<T: FromValue>(function: &str, args: &[Value], index: usize) -> JingResult<T> {
+    match args.get(index) {
+        Some(value) => convert_arg(function, index, value.clone()),
+        None => Err(JingError::runtime_error(format!(
+            "{}() missing argument {}",
+            function,
+            index + 1
+        ))),
+    }
+}
+
+/// Convert `args[index]` of builtin `function` if it was passed and not nil
+pub fn optional_arg<T: FromValue>(
+    function: &str,
+    args: &[Value],
+    index: usize,
+) -> JingResult<Option<T>> {
+    match args.get(index) {
+        Some(value) => convert_arg(function, index, value.clone()),
+        None => Ok(None),
+    }
+}
+
+impl FromValue for Value {
+    fn from_value(value: Value) -> JingResult<Self> {
+        Ok(value)
+    }
+
+    fn expected() -> String {
+        "any value".to_string()
+    }
+}
+
+impl IntoValue for Value {
+    fn into_value(self) -> Value {
+        self
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: Value) -> JingResult<Self> {
+        match value {
+            Value::Number(n) => Ok(n),
+            other => Err(type_mismatch("number", &other)),
+        }
+    }
+
+    fn expected() -> String {
+        "number".to_string()
+    }
+}
+
+impl IntoValue for f64 {
+    fn into_value(self) -> Value {
+        Value::Number(self)
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: Value) -> JingResult<Self> {
+        match value {
+            Value::Bool(b) => Ok(b),
+            other => Err(type_mismatch("bool", &other)),
+        }
+    }
+
+    fn expected() -> String {
+        "bool".to_string()
+    }
+}
+
+impl IntoValue for bool {
+    fn into_value(self) -> Value {
+        Value::Bool(self)
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: Value) -> JingResult<Self> {
+        match value {
+            Value::String(s) => Ok(s),
+            other => Err(type_mismatch("string", &other)),
+        }
+    }
+
+    fn expected() -> String {
+        "string".to_string()
+    }
+}
+
+impl IntoValue for String {
+    fn into_value(self) -> Value {
+        Value::String(self)
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: Value) -> JingResult<Self> {
+        match value {
+            Value::Number(n)
+                if n.fract() == 0.0 && n >= i64::MIN as f64 && n <= i64::MAX as f64 =>
+            {
+                Ok(n as i64)
+            }
+            Value::Number(n) => Err(JingError::type_error(format!(
+                "expected integer, got {}",
+                n
+            ))),
+            other => Err(type_mismatch("integer", &other)),
+        }
+    }
+
+    fn expected() -> String {
+        "integer".to_string()
+    }
+}
+
+impl IntoValue for i64 {
+    fn into_value(self) -> Value {
+        Value::Number(self as f64)
+    }
+}
+
+impl IntoValue for &str {
+    fn into_value(self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl IntoValue for () {
+    fn into_value(self) -> Value {
+        Value::Nil
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: Value) -> JingResult<Self> {
+        match value {
+            Value::Nil => Ok(None),
+            other => {
+                let type_name = other.type_name();
+                T::from_value(other).map(Some).map_err(|err| match err {
+                    JingError::TypeError { .. } => JingError::type_error(format!(
+                        "expected {}, got {}",
+                        Self::expected(),
+                        type_name
+                    )),
+                    other => other,
+                })
+            }
+        }
+    }
+
+    fn expected() -> String {
+        format!("{} or nil", T::expected())
+    }
+}
+
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn into_value(self) -> Value {
+        match self {
+            Some(value) => value.into_value(),
+            None => Value::Nil,
+        }
+    }
+}
+
+/// Prefix a nested conversion error with the location of the failing element
+fn nested_error(location: String, err: JingError) -> JingError {
+    match err {
+        JingError::TypeError { message } => {
+            JingError::type_error(format!("{} {}", location, message))
+        }
+        other => other,
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: Value) -> JingResult<Self> {
+        match value {
+            Value::List(items) => items
+                .into_iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    T::from_value(item)
+                        .map_err(|err| nested_error(format!("list element {}", i), err))
+                })
+                .collect(),
+            other => Err(type_mismatch(&Self::expected(), &other)),
+        }
+    }
+
+    fn expected() -> String {
+        format!("list of {}", T::expected())
+    }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn into_value(self) -> Value {
+        Value::List(self.into_iter().map(IntoValue::into_value).collect())
+    }
+}
+
+impl<T: FromValue> FromValue for BTreeMap<String, T> {
+    fn from_value(value: Value) -> JingResult<Self> {
+        match value {
+            Value::Map(entries) => entries
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = T::from_value(value)
+                        .map_err(|err| nested_error(format!("map value '{}'", key), err))?;
+                    Ok((key, value))
+                })
+                .collect(),
+            other => Err(type_mismatch(&Self::expected(), &other)),
+        }
+    }
+
+    fn expected() -> String {
+        format!("map of {}", T::expected())
+    }
+}
+
+impl<T: IntoValue> IntoValue for BTreeMap<String, T> {
+    fn into_value(self) -> Value {
+        Value::Map(
+            self.into_iter()
+                .map(|(key, value)| (key, value.into_value()))
+                .collect(),
+        )
+    }
+}
+
+impl<T: FromValue> FromValue for HashMap<String, T> {
+    fn from_value(value: Value) -> JingResult<Self> {
+        BTreeMap::<String, T>::from_value(value).map(|entries| entries.into_iter().collect())
+    }
+
+    fn expected() -> String {
+        BTreeMap::<String, T>::expected()
+    }
+}
+
+impl<T: IntoValue> IntoValue for HashMap<String, T> {
+    fn into_value(self) -> Value {
+        self.into_iter()
+            .collect::<BTreeMap<String, T>>()
+            .into_value()
+    }
+}
+
+/// Return values accepted from native functions: plain values or results
+pub trait IntoNativeResult {
+    fn into_native_result(self) -> JingResult<Value>;
+}
+
+impl<T: IntoValue> IntoNativeResult for T {
+    fn into_native_result(self) -> JingResult<Value> {
+        Ok(self.into_value())
+    }
+}
+
+impl<T: IntoValue> IntoNativeResult for JingResult<T> {
+    fn into_native_result(self) -> JingResult<Value> {
+        self.map(IntoValue::into_value)
+    }
+}
@@ -0,0 +1,309 @@
+//! Step debugger behind `jing debug`
+//!
+//! In debug mode the VM calls a `DebugHook` before each instruction. The
+//! `Debugger` hook uses the chunk's line table to stop at most once per
+//! statement: at a line breakpoint in the main script, or when a step ends.
+//! Steps compare call depths: `step` stops at the next statement wherever it
+//! is, `next` waits for a statement no deeper than the current one so calls
+//! run through, and `out` waits until the current function has returned.
+//!
+//! While stopped, commands are read from a prompt to inspect the value
+//! stack, the locals of the current frame and the globals. Functions only
+//! bind their parameters locally; `let` always assigns a global.
+
+use crate::error::{JingError, JingResult};
+use crate::value::Value;
+use crate::vm::VM;
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Write};
+
+/// Lines shown on each side of the current one by `list`
+const LIST_CONTEXT: usize = 3;
+
+/// Commands understood at the prompt: usage and description
+const COMMANDS: &[(&str, &str)] = &[
+    ("step, s", "Run to the next statement, entering calls"),
+    ("next, n", "Run to the next statement in this function"),
+    ("out, o", "Run until the current function returns"),
+    ("continue, c", "Run until a breakpoint"),
+    (
+        "break, b [line]",
+        "Set a breakpoint, or list them without a line",
+    ),
+    ("delete, d <line>", "Remove a breakpoint"),
+    ("print, p <name>", "Show a local or global variable"),
+    ("locals", "Show the current function's parameters"),
+    ("globals", "Show the global variables"),
+    ("stack", "Show the value stack, top last"),
+    ("backtrace, bt", "Show the call stack"),
+    ("list, l", "Show the source around the current line"),
+    ("quit, q", "Stop the script"),
+];
+
+/// Called by a VM in debug mode; see `VM::set_debug_hook`
+pub trait DebugHook {
+    /// Inspect the VM before it runs its next instruction
+    ///
+    /// Returning an error stops the script with that error.
+    fn before_instruction(&mut self, vm: &VM) -> JingResult<()>;
+}
+
+impl<F: FnMut(&VM) -> JingResult<()>> DebugHook for F {
+    fn before_instruction(&mut self, vm: &VM) -> JingResult<()> {
+        self(vm)
+    }
+}
+
+/// When to stop next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    /// At the next statement
+    Into,
+    /// At the next statement at this call depth or shallower
+    Over(usize),
+    /// At the next statement shallower than this call depth
+    Out(usize),
+    /// Only at breakpoints
+    Continue,
+}
+
+/// Interactive debugger reading commands from `input`
+///
+/// It stops at the first statement of the script, like `step` does.
+pub struct Debugger<R, W> {
+    input: R,
+    output: W,
+    /// Lines of the main script, for `list`
+    source: Vec<String>,
+    breakpoints: BTreeSet<usize>,
+    step: Step,
+    /// Repeated when an empty line is entered
+    last_command: Option<String>,
+}
+
+impl<R: BufRead, W: Write> Debugger<R, W> {
+    /// A debugger for the script `source`
+    pub fn new(source: &str, input: R, output: W) -> Self {
+        Debugger {
+            input,
+            output,
+            source: source.lines().map(str::to_string).collect(),
+            breakpoints: BTreeSet::new(),
+            step: Step::Into,
+            last_command: None,
+        }
+    }
+
+    /// Stop whenever the statement on `line` of the main script starts
+    pub fn set_breakpoint(&mut self, line: usize) {
+        self.breakpoints.insert(line);
+    }
+
+    /// Whether to stop at the statement on `line` that is about to start
+    fn should_stop(&self, vm: &VM, line: usize) -> bool {
+        let depth = vm.call_depth();
+        let stepped = match self.step {
+            Step::Into => true,
+            Step::Over(from) => depth <= from,
+            Step::Out(from) => depth < from,
+            Step::Continue => false,
+        };
+        stepped || (vm.in_main_script() && self.breakpoints.contains(&line))
+    }
+
+    /// Show where the script stopped and run commands until one resumes it
+    fn pause(&mut self, vm: &VM, line: usize) -> io::Result<JingResult<()>> {
+        writeln!(self.output, "Stopped at {}", describe(vm, line))?;
+        if vm.in_main_script() {
+            self.show_lines(line, line, line)?;
+        }
+
+        loop {
+            write!(self.output, "(jdb) ")?;
+            self.output.flush()?;
+
+            let mut input = String::new();
+            if self.input.read_line(&mut input)? == 0 {
+                writeln!(self.output)?;
+                return Ok(Err(JingError::exit(0)));
+            }
+            let input = input.trim();
+            let command = if input.is_empty() {
+                match self.last_command.clone() {
+                    Some(command) => command,
+                    None => continue,
+                }
+            } else {
+                self.last_command = Some(input.to_string());
+                input.to_string()
+            };
+
+            let (name, argument) = match command.split_once(char::is_whitespace) {
+                Some((name, argument)) => (name, argument.trim()),
+                None => (command.as_str(), ""),
+            };
+            let depth = vm.call_depth();
+            match name {
+                "s" | "step" => self.step = Step::Into,
+                "n" | "next" => self.step = Step::Over(depth),
+                "o" | "out" | "finish" => self.step = Step::Out(depth),
+                "c" | "continue" => self.step = Step::Continue,
+                "q" | "quit" => return Ok(Err(JingError::exit(0))),
+                _ => {
+                    self.inspect(vm, line, name, argument)?;
+                    continue;
+                }
+            }
+            return Ok(Ok(()));
+        }
+    }
+
+    /// Run a command that does not resume the script
+    fn inspect(&mut self, vm: &VM, line: usize, name: &str, argument: &str) -> io::Result<()> {
+        match name {
+            "b" | "break" if argument.is_empty() => {
+                if self.breakpoints.is_empty() {
+                    writeln!(self.output, "No breakpoints")?;
+                }
+                for line in &self.breakpoints {
+                    writeln!(self.output, "Breakpoint at line {}", line)?;
+                }
+            }
+            "b" | "break" => match argument.parse::<usize>() {
+                Ok(line) if line > 0 => {
+                    self.breakpoints.insert(line);
+                    writeln!(self.output, "Breakpoint set at line {}", line)?;
+                }
+                _ => writeln!(self.output, "Usage: break <line>")?,
+            },
+            "d" | "delete" => match argument.parse::<usize>() {
+                Ok(line) if self.breakpoints.remove(&line) => {
+                    writeln!(self.output, "Breakpoint at line {} removed", line)?;
+                }
+                Ok(line) => writeln!(self.output, "No breakpoint at line {}", line)?,
+                Err(_) => writeln!(self.output, "Usage: delete <line>")?,
+            },
+            "p" | "print" if argument.is_empty() => writeln!(self.output, "Usage: print <name>")?,
+            "p" | "print" => {
+                let locals = vm.frames().pop().map(|frame| frame.locals);
+                let value = locals
+                    .into_iter()
+                    .flatten()
+                    .chain(vm.current_globals())
+                    .find(|(name, _)| name == argument);
+                match value {
+                    Some((name, value)) => writeln!(self.output, "{} = {}", name, value.repr())?,
+                    None => writeln!(self.output, "No variable named '{}'", argument)?,
+                }
+            }
+            "locals" => {
+                let locals = vm.frames().pop().map(|frame| frame.locals);
+                self.show_values(&locals.unwrap_or_default(), "No locals")?;
+            }
+            "globals" => self.show_values(&vm.current_globals(), "No globals")?,
+            "stack" => {
+                let stack = vm.stack();
+                if stack.is_empty() {
+                    writeln!(self.output, "Stack is empty")?;
+                }
+                for (index, value) in stack.iter().enumerate() {
+                    writeln!(self.output, "[{}] {}", index, value.repr())?;
+                }
+            }
+            "bt" | "backtrace" => {
+                for (index, frame) in vm.frames().iter().rev().enumerate() {
+                    let function = match &frame.function {
+                        Some(name) => format!("{}()", name),
+                        None => "<top level>".to_string(),
+                    };
+                    let line = frame
+                        .line
+                        .map_or_else(|| "?".to_string(), |line| line.to_string());
+                    match &frame.module {
+                        Some(path) => writeln!(
+                            self.output,
+                            "#{} {} at {}:{}",
+                            index,
+                            function,
+                            path.display(),
+                            line
+                        )?,
+                        None => writeln!(self.output, "#{} {} at line {}", index, function, line)?,
+                    }
+                }
+            }
+            "l" | "list" if vm.in_main_script() => {
+                let first = line.saturating_sub(LIST_CONTEXT).max(1);
+                self.show_lines(first, line + LIST_CONTEXT, line)?;
+            }
+            "l" | "list" => writeln!(self.output, "No source for imported modules")?,
+            "h" | "help" => {
+                for (usage, description) in COMMANDS {
+                    writeln!(self.output, "  {:<18} {}", usage, description)?;
+                }
+                writeln!(self.output, "An empty line repeats the last command.")?;
+            }
+            other => writeln!(
+                self.output,
+                "Unknown command '{}'; type 'help' for a list",
+                other
+            )?,
+        }
+        Ok(())
+    }
+
+    /// Print source lines `first..=last`, marking `current`
+    fn show_lines(&mut self, first: usize, last: usize, current: usize) -> io::Result<()> {
+        for number in first..=last.min(self.source.len()) {
+            let marker = if number == current { "->" } else { "  " };
+            writeln!(
+                self.output,
+                "{} {:>4} | {}",
+                marker,
+                number,
+                self.source[number - 1]
+            )?;
+        }
+        Ok(())
+    }
+
+    fn show_values(&mut self, values: &[(String, Value)], empty: &str) -> io::Result<()> {
+        if values.is_empty() {
+            writeln!(self.output, "{}", empty)?;
+        }
+        for (name, value) in values {
+            writeln!(self.output, "{} = {}", name, value.repr())?;
+        }
+        Ok(())
+    }
+}
+
+impl<R: BufRead, W: Write> DebugHook for Debugger<R, W> {
+    fn before_instruction(&mut self, vm: &VM) -> JingResult<()> {
+        let Some(line) = vm.line_start() else {
+            return Ok(());
+        };
+        if !self.should_stop(vm, line) {
+            return Ok(());
+        }
+        self.pause(vm, line).unwrap_or_else(|err| {
+            Err(JingError::io_error(format!(
+                "Debugger could not use the terminal: {}",
+                err
+            )))
+        })
+    }
+}
+
+/// `line 3 in area()`, naming the module for code that was imported
+fn describe(vm: &VM, line: usize) -> String {
+    let frame = vm.frames().pop();
+    let mut text = format!("line {}", line);
+    if let Some(path) = frame.as_ref().and_then(|frame| frame.module.as_ref()) {
+        text.push_str(&format!(" of {}", path.display()));
+    }
+    if let Some(function) = frame.and_then(|frame| frame.function) {
+        text.push_str(&format!(" in {}()", function));
+    }
+    text
+}
@@ -15,6 +15,14 @@ pub enum JingError {
     TypeError { message: String },
     /// I/O errors
     IoError { message: String },
+    /// A configured resource limit (such as the memory limit) was exceeded
+    ResourceExhausted { message: String },
+    /// An operation was blocked by the VM's sandbox capabilities
+    PermissionDenied { message: String },
+    /// An `assert`, `assert_eq` or `assert_ne` check failed
+    AssertionFailed { message: String },
+    /// The script called `exit(code)` to stop with a process exit status
+    Exit { code: i32 },
 }
 
 impl fmt::Display for JingError {
@@ -38,6 +46,41 @@ impl fmt::Display for JingError {
             JingError::IoError { message } => {
                 write!(f, "I/O error: {}", message)
             }
+            JingError::ResourceExhausted { message } => {
+                write!(f, "Resource exhausted: {}", message)
+            }
+            JingError::PermissionDenied { message } => {
+                write!(f, "Permission denied: {}", message)
+            }
+            JingError::AssertionFailed { message } => {
+                write!(f, "Assertion failed: {}", message)
+            }
+            JingError::Exit { code } => {
+                write!(f, "Exited with status {}", code)
+            }
+        }
+    }
+}
+
+impl JingError {
+    /// Process exit status for this error, following the BSD `sysexits` codes
+    ///
+    /// Errors in the program text exit with 65 (`EX_DATAERR`), failures while
+    /// running (including failed assertions) with 70 (`EX_SOFTWARE`), I/O
+    /// errors with 74 (`EX_IOERR`) and sandbox violations with 77
+    /// (`EX_NOPERM`). A script that calls `exit(code)` exits with `code`.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            JingError::LexError { .. }
+            | JingError::ParseError { .. }
+            | JingError::CompileError { .. } => 65,
+            JingError::RuntimeError { .. }
+            | JingError::TypeError { .. }
+            | JingError::ResourceExhausted { .. }
+            | JingError::AssertionFailed { .. } => 70,
+            JingError::IoError { .. } => 74,
+            JingError::PermissionDenied { .. } => 77,
+            JingError::Exit { code } => *code,
         }
     }
 }
@@ -86,4 +129,26 @@ impl JingError {
             message: message.into(),
         }
     }
+
+    pub fn resource_exhausted(message: impl Into<String>) -> Self {
+        JingError::ResourceExhausted {
+            message: message.into(),
+        }
+    }
+
+    pub fn assertion_failed(message: impl Into<String>) -> Self {
+        JingError::AssertionFailed {
+            message: message.into(),
+        }
+    }
+
+    pub fn exit(code: i32) -> Self {
+        JingError::Exit { code }
+    }
+
+    pub fn permission_denied(message: impl Into<String>) -> Self {
+        JingError::PermissionDenied {
+            message: message.into(),
+        }
+    }
 }
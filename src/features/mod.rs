@@ -5,7 +5,69 @@
 //! builtin functions without touching core implementation files.
 
 use crate::error::JingResult;
+use crate::sandbox::Capabilities;
 use crate::value::Value;
+use crate::vm::VM;
+use std::fmt;
+
+/// Number of arguments a builtin function accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Arity {
+    pub min: usize,
+    /// Maximum number of arguments, or `None` for variadic functions
+    pub max: Option<usize>,
+}
+
+impl Arity {
+    /// Exactly `n` arguments
+    pub fn exact(n: usize) -> Self {
+        Arity {
+            min: n,
+            max: Some(n),
+        }
+    }
+
+    /// Between `min` and `max` arguments (inclusive), for optional arguments
+    pub fn range(min: usize, max: usize) -> Self {
+        Arity {
+            min,
+            max: Some(max),
+        }
+    }
+
+    /// `min` or more arguments
+    pub fn at_least(min: usize) -> Self {
+        Arity { min, max: None }
+    }
+
+    /// Check if a call with `count` arguments is allowed
+    pub fn accepts(&self, count: usize) -> bool {
+        count >= self.min && self.max.map_or(true, |max| count <= max)
+    }
+
+    /// The accepted counts as a phrase, such as "1 argument" or "any
+    /// number of arguments"
+    pub fn describe(&self) -> String {
+        let noun = |count: usize| if count == 1 { "argument" } else { "arguments" };
+        match self.max {
+            None if self.min == 0 => "any number of arguments".to_string(),
+            Some(0) => "no arguments".to_string(),
+            None => format!("at least {} {}", self.min, noun(self.min)),
+            Some(max) if max == self.min => format!("{} {}", max, noun(max)),
+            Some(max) => format!("{} to {} arguments", self.min, max),
+        }
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{}", max),
+            Some(max) => write!(f, "{} to {}", self.min, max),
+            None => write!(f, "at least {}", self.min),
+        }
+    }
+}
 
 /// Trait for builtin functions that can be dynamically registered
 ///
@@ -15,14 +77,84 @@ pub trait BuiltinFunction: Send + Sync + std::fmt::Debug {
     /// Name of the function as it appears in Jing code
     fn name(&self) -> &str;
 
-    /// Number of parameters this function expects
-    fn arity(&self) -> usize;
+    /// Number of parameters this function accepts
+    fn arity(&self) -> Arity;
 
     /// Execute the function with the given arguments
     fn call(&self, args: Vec<Value>) -> JingResult<Value>;
 
+    /// Execute the function with access to the calling VM
+    ///
+    /// The VM always calls builtins through this method. Higher-order
+    /// builtins override it to call back into Jing functions via `context`;
+    /// the default runs `call_with_capabilities` under the VM's sandbox.
+    fn call_with_context(&self, args: Vec<Value>, context: &mut CallContext) -> JingResult<Value> {
+        self.call_with_capabilities(args, context.capabilities())
+    }
+
+    /// Execute the function under the calling VM's sandbox capabilities
+    ///
+    /// Builtins that access the host (files, stdin) override this to
+    /// check `capabilities` first. The default ignores the capabilities.
+    fn call_with_capabilities(
+        &self,
+        args: Vec<Value>,
+        _capabilities: &Capabilities,
+    ) -> JingResult<Value> {
+        self.call(args)
+    }
+
     /// Help text for the function (used in documentation/REPL help)
     fn help(&self) -> &str {
         "No help available"
     }
 }
+
+/// Access to the calling VM for builtins that need more than their arguments
+pub struct CallContext<'a> {
+    vm: &'a mut VM,
+}
+
+impl<'a> CallContext<'a> {
+    pub fn new(vm: &'a mut VM) -> Self {
+        CallContext { vm }
+    }
+
+    /// Call a Jing function or builtin value with the given arguments
+    pub fn call(&mut self, function: &Value, args: Vec<Value>) -> JingResult<Value> {
+        self.vm.call_value(function.clone(), args)
+    }
+
+    /// Get a global variable by name
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        self.vm.get_global(name)
+    }
+
+    /// Get the command-line arguments passed to the script
+    pub fn args(&self) -> &[String] {
+        self.vm.args()
+    }
+
+    /// Get an environment variable, including those set by the script
+    pub fn env_var(&self, name: &str) -> Option<String> {
+        self.vm.env_var(name)
+    }
+
+    /// Set an environment variable for the calling VM only
+    pub fn set_env_var(&mut self, name: &str, value: &str) {
+        self.vm.set_env_var(name, value)
+    }
+
+    /// Get the sandbox capabilities of the calling VM
+    pub fn capabilities(&self) -> &Capabilities {
+        self.vm.capabilities()
+    }
+
+    /// Fail if allocating `bytes` would exceed the calling VM's memory limit
+    ///
+    /// Call this before building a large result; the result itself is
+    /// counted when the builtin returns it.
+    pub fn check_allocation(&self, bytes: usize) -> JingResult<()> {
+        self.vm.check_allocation(bytes)
+    }
+}
@@ -0,0 +1,358 @@
+//! Pretty-printer behind `jing fmt`
+//!
+//! Source is parsed and the AST is printed back in one canonical style:
+//! four-space indentation, one statement per line, single spaces around
+//! binary operators and opening braces on the same line as the statement
+//! they belong to. Parentheses are only written where precedence needs
+//! them. The AST has no comments, so they come from the lexer's trivia and
+//! are put back by line number, as are single blank lines between
+//! statements. A comment after the last statement on a line goes with the
+//! outermost statement ending there, so it stays after a closing brace.
+//!
+//! Bodies of `if`, `else` and `while` written without braces are left
+//! without them, on the same line as their condition; adding braces would
+//! change the syntax tree.
+
+use crate::error::JingResult;
+use crate::lexer::{Comment, Lexer};
+use crate::parser::{
+    default_alias, BinaryOperator, BlockStmt, Expr, IfStmt, LineSpan, LiteralValue,
+    LogicalOperator, Param, Parser, Stmt, UnaryOperator,
+};
+
+/// Indentation for one level of nesting
+pub const INDENT: &str = "    ";
+
+/// Format a program, keeping its comments and a leading `#!` line
+pub fn format_source(source: &str) -> JingResult<String> {
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize()?;
+    let mut parser = Parser::new(tokens);
+    let statements = parser.parse()?;
+
+    let mut formatter = Formatter {
+        comments: lexer.comments(),
+        next_comment: 0,
+        statement_spans: parser.statement_spans(),
+        next_statement: 0,
+        block_spans: parser.block_spans(),
+        next_block: 0,
+        out: String::new(),
+        enclosing_ends: Vec::new(),
+        depth: 0,
+        last_line: None,
+    };
+
+    if source.starts_with("#!") {
+        let shebang = source.lines().next().unwrap_or_default();
+        formatter.out.push_str(shebang.trim_end());
+        formatter.out.push('\n');
+        formatter.last_line = Some(1);
+    }
+    formatter.statements(&statements, usize::MAX);
+    Ok(formatter.out)
+}
+
+/// Writes statements while walking the parser's spans in step with the AST
+///
+/// The spans are listed in the order statements and blocks start, which is
+/// the order the formatter visits them.
+struct Formatter<'a> {
+    comments: &'a [Comment],
+    next_comment: usize,
+    statement_spans: &'a [LineSpan],
+    next_statement: usize,
+    block_spans: &'a [LineSpan],
+    next_block: usize,
+    out: String,
+    /// Last source lines of the statements being written, outermost first
+    enclosing_ends: Vec<usize>,
+    depth: usize,
+    /// Source line of the last statement or comment written; `None` at the
+    /// start of a block, where blank lines are dropped
+    last_line: Option<usize>,
+}
+
+impl Formatter<'_> {
+    /// Write a list of statements followed by the comments before `end`
+    fn statements(&mut self, statements: &[Stmt], end: usize) {
+        for statement in statements {
+            let span = self.statement_spans[self.next_statement];
+            self.next_statement += 1;
+
+            self.comments_before(span.start);
+            self.blank_line_before(span.start);
+            self.indent();
+            // A statement ending on the same line as one around it leaves
+            // the line's comment to that statement
+            let outermost = !self.enclosing_ends.contains(&span.end);
+            self.enclosing_ends.push(span.end);
+            self.statement(statement);
+            self.enclosing_ends.pop();
+            if outermost {
+                self.trailing_comments(span.end);
+            } else {
+                self.out.push('\n');
+            }
+            self.last_line = Some(span.end);
+        }
+        self.comments_before(end);
+    }
+
+    /// Write the comments that start before `line` on lines of their own
+    fn comments_before(&mut self, line: usize) {
+        while let Some(comment) = self.comments.get(self.next_comment) {
+            if comment.line >= line {
+                break;
+            }
+            self.next_comment += 1;
+            self.blank_line_before(comment.line);
+            self.indent();
+            self.out.push_str(&format!("//{}\n", comment.text));
+            // Comments moved below a multi-line expression come before its end
+            self.last_line = self.last_line.max(Some(comment.line));
+        }
+    }
+
+    /// End a statement, keeping a comment that followed it on its last line
+    ///
+    /// Comments from inside a multi-line expression are moved below it.
+    fn trailing_comments(&mut self, end: usize) {
+        if let Some(comment) = self.comments.get(self.next_comment) {
+            if comment.line == end && comment.trailing {
+                self.next_comment += 1;
+                self.out.push_str(&format!(" //{}", comment.text));
+            }
+        }
+        self.out.push('\n');
+    }
+
+    /// Keep one blank line where the source had at least one
+    fn blank_line_before(&mut self, line: usize) {
+        if self.last_line.is_some_and(|last| line > last + 1) {
+            self.out.push('\n');
+        }
+    }
+
+    fn indent(&mut self) {
+        for _ in 0..self.depth {
+            self.out.push_str(INDENT);
+        }
+    }
+
+    fn statement(&mut self, statement: &Stmt) {
+        match statement {
+            Stmt::Expression(stmt) => {
+                self.out.push_str(&expression(&stmt.expr));
+                self.out.push(';');
+            }
+            Stmt::Let(stmt) => {
+                self.out.push_str(&format!(
+                    "let {} = {};",
+                    stmt.name,
+                    expression(&stmt.initializer)
+                ));
+            }
+            Stmt::Block(block) => self.block(block),
+            Stmt::If(stmt) => self.if_statement(stmt),
+            Stmt::While(stmt) => {
+                self.out
+                    .push_str(&format!("while {} ", expression(&stmt.condition)));
+                self.statement(&stmt.body);
+            }
+            Stmt::Function(stmt) => {
+                let params: Vec<String> = stmt.params.iter().map(parameter).collect();
+                self.out
+                    .push_str(&format!("fn {}({}) ", stmt.name, params.join(", ")));
+                self.statement(&stmt.body);
+            }
+            Stmt::Return(stmt) => match &stmt.value {
+                Some(value) => self.out.push_str(&format!("return {};", expression(value))),
+                None => self.out.push_str("return;"),
+            },
+            Stmt::Print(stmt) => {
+                self.out
+                    .push_str(&format!("print({});", expression(&stmt.expr)));
+            }
+            Stmt::Import(stmt) => {
+                self.out.push_str(&format!("import {}", string(&stmt.path)));
+                if default_alias(&stmt.path).as_deref() != Some(stmt.alias.as_str()) {
+                    self.out.push_str(&format!(" as {}", stmt.alias));
+                }
+                self.out.push(';');
+            }
+            Stmt::Export(stmt) => {
+                self.out.push_str("export ");
+                self.statement(&stmt.declaration);
+            }
+        }
+    }
+
+    fn if_statement(&mut self, stmt: &IfStmt) {
+        self.out
+            .push_str(&format!("if {} ", expression(&stmt.condition)));
+        self.statement(&stmt.then_branch);
+
+        // An `else if` chain stays on the closing brace's line
+        if let Some(else_branch) = &stmt.else_branch {
+            self.out.push_str(" else ");
+            self.statement(else_branch);
+        }
+    }
+
+    fn block(&mut self, block: &BlockStmt) {
+        let span = self.block_spans[self.next_block];
+        self.next_block += 1;
+
+        let has_comments = self
+            .comments
+            .get(self.next_comment)
+            .is_some_and(|comment| comment.line < span.end);
+        if block.statements.is_empty() && !has_comments {
+            self.out.push_str("{}");
+            return;
+        }
+
+        self.out.push_str("{\n");
+        self.depth += 1;
+        self.last_line = None;
+        self.statements(&block.statements, span.end);
+        self.depth -= 1;
+        self.indent();
+        self.out.push('}');
+    }
+}
+
+/// Binding strength of an expression, higher binds tighter
+fn precedence(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Assign(_) => 1,
+        Expr::Logical(logical) => match logical.operator {
+            LogicalOperator::Or => 2,
+            LogicalOperator::And => 3,
+        },
+        Expr::Binary(binary) => match binary.operator {
+            BinaryOperator::Equal | BinaryOperator::NotEqual => 4,
+            BinaryOperator::Less
+            | BinaryOperator::LessEqual
+            | BinaryOperator::Greater
+            | BinaryOperator::GreaterEqual => 5,
+            BinaryOperator::Add | BinaryOperator::Subtract => 6,
+            BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Modulo => 7,
+        },
+        Expr::Unary(_) => 8,
+        Expr::Call(_) | Expr::Get(_) | Expr::Literal(_) | Expr::Variable(_) => 9,
+    }
+}
+
+/// Format `expr` as an operand that must bind at least as tightly as `min`
+fn operand(expr: &Expr, min: u8) -> String {
+    if precedence(expr) < min {
+        format!("({})", expression(expr))
+    } else {
+        expression(expr)
+    }
+}
+
+/// Format an expression on a single line
+pub fn expression(expr: &Expr) -> String {
+    let strength = precedence(expr);
+    match expr {
+        Expr::Literal(literal) => match &literal.value {
+            LiteralValue::Number(value) => value.to_string(),
+            LiteralValue::String(value) => string(value),
+            LiteralValue::Bool(value) => value.to_string(),
+            LiteralValue::Nil => "nil".to_string(),
+        },
+        Expr::Variable(variable) => variable.name.clone(),
+        Expr::Assign(assign) => format!("{} = {}", assign.name, expression(&assign.value)),
+        // Operators are left-associative, so only the right operand needs
+        // parentheses at the same precedence
+        Expr::Binary(binary) => format!(
+            "{} {} {}",
+            operand(&binary.left, strength),
+            binary_operator(&binary.operator),
+            operand(&binary.right, strength + 1)
+        ),
+        Expr::Logical(logical) => {
+            let operator = match logical.operator {
+                LogicalOperator::And => "&&",
+                LogicalOperator::Or => "||",
+            };
+            format!(
+                "{} {} {}",
+                operand(&logical.left, strength),
+                operator,
+                operand(&logical.right, strength + 1)
+            )
+        }
+        Expr::Unary(unary) => {
+            let operator = match unary.operator {
+                UnaryOperator::Minus => "-",
+                UnaryOperator::Not => "!",
+            };
+            let operand = operand(&unary.operand, strength);
+            if operator == "-" && operand.starts_with('-') {
+                format!("-({})", operand)
+            } else {
+                format!("{}{}", operator, operand)
+            }
+        }
+        Expr::Call(call) => {
+            let args = call
+                .args
+                .iter()
+                .map(expression)
+                .chain(
+                    call.named
+                        .iter()
+                        .map(|arg| format!("{}: {}", arg.name, expression(&arg.value))),
+                )
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{}({})", operand(&call.callee, strength), args)
+        }
+        Expr::Get(get) => format!("{}.{}", operand(&get.object, strength), get.name),
+    }
+}
+
+fn binary_operator(operator: &BinaryOperator) -> &'static str {
+    match operator {
+        BinaryOperator::Add => "+",
+        BinaryOperator::Subtract => "-",
+        BinaryOperator::Multiply => "*",
+        BinaryOperator::Divide => "/",
+        BinaryOperator::Modulo => "%",
+        BinaryOperator::Equal => "==",
+        BinaryOperator::NotEqual => "!=",
+        BinaryOperator::Less => "<",
+        BinaryOperator::LessEqual => "<=",
+        BinaryOperator::Greater => ">",
+        BinaryOperator::GreaterEqual => ">=",
+    }
+}
+
+fn parameter(param: &Param) -> String {
+    let rest = if param.rest { "..." } else { "" };
+    match &param.default {
+        Some(default) => format!("{}{} = {}", rest, param.name, expression(default)),
+        None => format!("{}{}", rest, param.name),
+    }
+}
+
+/// A string literal with the escapes the lexer understands
+fn string(value: &str) -> String {
+    let mut literal = String::from('"');
+    for c in value.chars() {
+        match c {
+            '\n' => literal.push_str("\\n"),
+            '\t' => literal.push_str("\\t"),
+            '\r' => literal.push_str("\\r"),
+            '\\' => literal.push_str("\\\\"),
+            '"' => literal.push_str("\\\""),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
@@ -1,4 +1,5 @@
 use crate::error::{JingError, JingResult};
+use crate::syntax::{SyntaxToken, TokenKind};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenType {
@@ -20,6 +21,9 @@ pub enum TokenType {
     And,
     Or,
     Not,
+    Import,
+    Export,
+    As,
 
     // Operators
     Plus,
@@ -43,53 +47,237 @@ pub enum TokenType {
     RightBrace,
     Semicolon,
     Comma,
+    Colon,
+    Dot,
+    Ellipsis,
 
     // Special
     Newline,
     Eof,
 }
 
+/// Byte range `start..end` of a piece of source text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Whether `offset` falls inside the span or at its end
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset <= self.end
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub token_type: TokenType,
     pub line: usize,
+    /// Bytes of the source the token was read from; empty for tokens made
+    /// by hand
+    pub span: Span,
 }
 
 impl Token {
     pub fn new(token_type: TokenType, line: usize) -> Self {
-        Token { token_type, line }
+        Token {
+            token_type,
+            line,
+            span: Span::default(),
+        }
     }
 }
 
+/// A `//` comment, kept as trivia next to the token stream
+///
+/// The parser never sees comments; tools such as the formatter put them
+/// back by line number.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    /// Text after the `//`, without trailing whitespace
+    pub text: String,
+    pub line: usize,
+    /// Bytes of the whole comment, from `//` to the end of the line
+    pub span: Span,
+    /// Whether code precedes the comment on the same line
+    pub trailing: bool,
+}
+
+/// Reserved words and the tokens they produce
+pub const KEYWORDS: &[(&str, TokenType)] = &[
+    ("let", TokenType::Let),
+    ("if", TokenType::If),
+    ("else", TokenType::Else),
+    ("while", TokenType::While),
+    ("fn", TokenType::Fn),
+    ("return", TokenType::Return),
+    ("true", TokenType::True),
+    ("false", TokenType::False),
+    ("nil", TokenType::Nil),
+    ("and", TokenType::And),
+    ("or", TokenType::Or),
+    ("not", TokenType::Not),
+    ("import", TokenType::Import),
+    ("export", TokenType::Export),
+    ("as", TokenType::As),
+];
+
 pub struct Lexer {
     input: Vec<char>,
+    /// Byte offset of each character, plus one entry for the end of input
+    offsets: Vec<usize>,
     current: usize,
+    /// Character index where the token being read starts
+    token_start: usize,
     line: usize,
+    comments: Vec<Comment>,
+    /// Line on which the last token other than a newline ended
+    last_token_line: Option<usize>,
+    /// Text skipped by `tokenize_tolerant` because it is not a token
+    invalid: Vec<Span>,
 }
 
 impl Lexer {
     pub fn new(input: &str) -> Self {
+        let input: Vec<char> = input.chars().collect();
+        let offsets = input
+            .iter()
+            .scan(0, |offset, c| {
+                let start = *offset;
+                *offset += c.len_utf8();
+                Some(start)
+            })
+            .chain(std::iter::once(input.iter().map(|c| c.len_utf8()).sum()))
+            .collect();
+
+        // Skip a `#!/usr/bin/env jing` line so scripts can be executable
+        let current = if input.starts_with(&['#', '!']) {
+            input.iter().position(|&c| c == '\n').unwrap_or(input.len())
+        } else {
+            0
+        };
+
         Lexer {
-            input: input.chars().collect(),
-            current: 0,
+            input,
+            offsets,
+            current,
+            token_start: current,
             line: 1,
+            comments: Vec::new(),
+            last_token_line: None,
+            invalid: Vec::new(),
         }
     }
 
+    /// Comments found so far, in source order
+    pub fn comments(&self) -> &[Comment] {
+        &self.comments
+    }
+
     /// Tokenize the entire input
     pub fn tokenize(&mut self) -> JingResult<Vec<Token>> {
+        self.scan(None)
+    }
+
+    /// Tokenize the entire input, skipping text that is not a token
+    ///
+    /// The skipped text becomes an error token in `lossless`. The first
+    /// error is returned next to the tokens.
+    pub(crate) fn tokenize_tolerant(&mut self) -> (Vec<Token>, Option<JingError>) {
+        let mut errors = Vec::new();
+        let tokens = self.scan(Some(&mut errors)).expect("errors are collected");
+        (tokens, errors.into_iter().next())
+    }
+
+    /// Read all tokens, collecting errors into `errors` when given
+    fn scan(&mut self, mut errors: Option<&mut Vec<JingError>>) -> JingResult<Vec<Token>> {
         let mut tokens = Vec::new();
 
         while !self.is_at_end() {
-            if let Some(token) = self.next_token()? {
-                tokens.push(token);
+            match self.next_token() {
+                Ok(Some(mut token)) => {
+                    if token.token_type != TokenType::Newline {
+                        self.last_token_line = Some(self.line);
+                    }
+                    token.span = self.span_from(self.token_start);
+                    tokens.push(token);
+                }
+                Ok(None) => {}
+                Err(err) => match errors.as_deref_mut() {
+                    Some(errors) => {
+                        self.invalid.push(self.span_from(self.token_start));
+                        errors.push(err);
+                    }
+                    None => return Err(err),
+                },
             }
         }
 
-        tokens.push(Token::new(TokenType::Eof, self.line));
+        let mut eof = Token::new(TokenType::Eof, self.line);
+        eof.span = self.span_from(self.current);
+        tokens.push(eof);
         Ok(tokens)
     }
 
+    /// Tokenize the entire input without losing any of it
+    ///
+    /// Whitespace, newlines, comments and a leading `#!` line are returned
+    /// as trivia between the tokens, so joining the texts of the returned
+    /// tokens gives back the input exactly. The end-of-input token is left
+    /// out.
+    pub fn tokenize_lossless(&mut self) -> JingResult<Vec<SyntaxToken>> {
+        let tokens = self.tokenize()?;
+        Ok(self.lossless(&tokens))
+    }
+
+    /// Interleave `tokens`, as returned by `tokenize` or
+    /// `tokenize_tolerant`, with the trivia between them
+    pub(crate) fn lossless(&self, tokens: &[Token]) -> Vec<SyntaxToken> {
+        let source: String = self.input.iter().collect();
+
+        let mut invalid = self.invalid.iter().peekable();
+        let mut lossless = Vec::new();
+        let mut offset = 0;
+        for token in tokens {
+            while let Some(span) = invalid.next_if(|span| span.start < token.span.start) {
+                push_trivia(&source, offset, span.start, &mut lossless);
+                lossless.push(SyntaxToken::new(TokenKind::Error, &source, *span));
+                offset = span.end;
+            }
+            push_trivia(&source, offset, token.span.start, &mut lossless);
+            offset = token.span.end;
+            match &token.token_type {
+                TokenType::Eof => break,
+                TokenType::Newline => {
+                    lossless.push(SyntaxToken::new(TokenKind::Newline, &source, token.span))
+                }
+                token_type => lossless.push(SyntaxToken::new(
+                    TokenKind::Token(token_type.clone()),
+                    &source,
+                    token.span,
+                )),
+            }
+        }
+        lossless
+    }
+
+    /// Byte span from the character at `start` to the current position
+    fn span_from(&self, start: usize) -> Span {
+        Span::new(self.offsets[start], self.offsets[self.current])
+    }
     /// Get the next token
     fn next_token(&mut self) -> JingResult<Option<Token>> {
         self.skip_whitespace();
@@ -99,6 +287,7 @@ impl Lexer {
         }
 
         let start_line = self.line;
+        self.token_start = self.current;
         let c = self.advance();
 
         match c {
@@ -108,15 +297,33 @@ impl Lexer {
             '}' => Ok(Some(Token::new(TokenType::RightBrace, start_line))),
             ';' => Ok(Some(Token::new(TokenType::Semicolon, start_line))),
             ',' => Ok(Some(Token::new(TokenType::Comma, start_line))),
+            ':' => Ok(Some(Token::new(TokenType::Colon, start_line))),
+            '.' => {
+                if self.peek() == '.' && self.peek_next() == '.' {
+                    self.advance();
+                    self.advance();
+                    Ok(Some(Token::new(TokenType::Ellipsis, start_line)))
+                } else {
+                    Ok(Some(Token::new(TokenType::Dot, start_line)))
+                }
+            }
             '+' => Ok(Some(Token::new(TokenType::Plus, start_line))),
             '-' => Ok(Some(Token::new(TokenType::Minus, start_line))),
             '*' => Ok(Some(Token::new(TokenType::Star, start_line))),
             '/' => {
                 if self.match_char('/') {
                     // Single-line comment
+                    let start = self.current;
                     while self.peek() != '\n' && !self.is_at_end() {
                         self.advance();
                     }
+                    let text: String = self.input[start..self.current].iter().collect();
+                    self.comments.push(Comment {
+                        text: text.trim_end().to_string(),
+                        line: start_line,
+                        span: self.span_from(start - 2),
+                        trailing: self.last_token_line == Some(start_line),
+                    });
                     self.next_token()
                 } else {
                     Ok(Some(Token::new(TokenType::Slash, start_line)))
@@ -257,21 +464,12 @@ impl Lexer {
 
         let text: String = self.input[start..self.current].iter().collect();
 
-        let token_type = match text.as_str() {
-            "let" => TokenType::Let,
-            "if" => TokenType::If,
-            "else" => TokenType::Else,
-            "while" => TokenType::While,
-            "fn" => TokenType::Fn,
-            "return" => TokenType::Return,
-            "true" => TokenType::True,
-            "false" => TokenType::False,
-            "nil" => TokenType::Nil,
-            "and" => TokenType::And,
-            "or" => TokenType::Or,
-            "not" => TokenType::Not,
-            _ => TokenType::Identifier(text),
-        };
+        let token_type = KEYWORDS
+            .iter()
+            .find(|(keyword, _)| *keyword == text)
+            .map_or(TokenType::Identifier(text), |(_, token_type)| {
+                token_type.clone()
+            });
 
         Ok(Some(Token::new(token_type, start_line)))
     }
@@ -333,6 +531,35 @@ impl Lexer {
     }
 }
 
+/// Split the gap `start..end` between two tokens into trivia
+///
+/// Gaps only hold whitespace other than newlines (which are tokens), `//`
+/// comments and the `#!` line at the very start.
+fn push_trivia(source: &str, start: usize, end: usize, out: &mut Vec<SyntaxToken>) {
+    let mut offset = start;
+    while offset < end {
+        let rest = &source[offset..end];
+        let (kind, len) = if offset == 0 && rest.starts_with("#!") {
+            (TokenKind::Shebang, rest.find('\n').unwrap_or(rest.len()))
+        } else if rest.starts_with("//") {
+            (TokenKind::Comment, rest.find('\n').unwrap_or(rest.len()))
+        } else {
+            let len = rest
+                .find(|c: char| !c.is_whitespace() || c == '\n')
+                .unwrap_or(rest.len());
+            // Only reached for a newline that the lexer skipped, which
+            // cannot happen, but never loop forever on one
+            (TokenKind::Whitespace, len.max(1))
+        };
+        out.push(SyntaxToken::new(
+            kind,
+            source,
+            Span::new(offset, offset + len),
+        ));
+        offset += len;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -366,6 +593,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_shebang_line_is_skipped() {
+        let mut lexer = Lexer::new("#!/usr/bin/env jing\nlet x = 1;");
+        let tokens = lexer.tokenize().unwrap();
+
+        assert_eq!(tokens[0].token_type, TokenType::Newline);
+        assert_eq!(tokens[1].token_type, TokenType::Let);
+        assert_eq!(tokens[1].line, 2);
+
+        // Only a shebang on the very first line is ignored
+        assert!(Lexer::new("let x = 1;\n#!jing").tokenize().is_err());
+    }
+
     #[test]
     fn test_number_literal() {
         let mut lexer = Lexer::new("123.45");
@@ -16,15 +16,24 @@
 //! easy extension without modifying core files:
 //!
 //! - **Features**: Trait-based system for language features
-//! - **Registry**: Central registration system for all extensions  
+//! - **Registry**: Per-VM registration system for builtin functions
 //! - **Builtins**: Modular builtin function system
 //! - **Operators**: Pluggable operator system
 //!
 //! ## Adding New Features
 //!
-//! To add a new builtin function:
+//! To add a new builtin function from host code, register a closure:
+//!
+//! ```rust
+//! let mut vm = jing::VM::new();
+//! vm.register_fn("hypot", |a: f64, b: f64| (a * a + b * b).sqrt());
+//! ```
+//!
+//! Arguments and results are converted with `FromValue`/`IntoValue`.
+//! For builtins that ship with the language:
 //! 1. Implement the `BuiltinFunction` trait
-//! 2. Register it using `register_builtin!` or in an init function
+//! 2. Register it in `builtins::register_defaults`, or on a single VM
+//!    with `vm.registry_mut().register(...)`
 //! 3. Done! No need to touch existing files
 //!
 //! To add a new operator:
@@ -37,9 +46,6 @@
 //! ```rust
 //! use jing::{Lexer, Parser, Compiler, VM};
 //!
-//! // Initialize the language extensions
-//! jing::init();
-//!
 //! let input = "let x = 42; print(x + 8);";
 //!
 //! let mut lexer = Lexer::new(input);
@@ -56,10 +62,20 @@
 //! ```
 
 // Core modules
+pub mod cli;
 pub mod compiler;
+pub mod convert;
+pub mod debugger;
 pub mod error;
+pub mod formatter;
 pub mod lexer;
+pub mod lsp;
+pub mod module;
 pub mod parser;
+pub mod repl;
+pub mod sandbox;
+pub mod syntax;
+pub mod testing;
 pub mod value;
 pub mod vm;
 
@@ -70,19 +86,20 @@ pub mod registry;
 
 // Public re-exports for easy access
 pub use compiler::{Chunk, Compiler, OpCode};
+pub use convert::{FromValue, IntoValue};
 pub use error::{JingError, JingResult};
 pub use lexer::{Lexer, Token};
 pub use parser::{Expr, Parser, Stmt};
+pub use registry::Registry;
+pub use sandbox::{Capabilities, FileAccess};
 pub use value::{Environment, Value};
 pub use vm::VM;
 
 // Feature system
-pub use features::BuiltinFunction;
+pub use features::{Arity, BuiltinFunction, CallContext};
 
 /// Initialize the Jing language with all built-in features.
 ///
-/// This function registers all built-in functions and other
-/// language features. Call this before using the language.
-pub fn init() {
-    builtins::init_builtins();
-}
+/// Builtins are now registered per VM (see `Registry::with_defaults`),
+/// so this is a no-op kept for compatibility with existing hosts.
+pub fn init() {}
@@ -0,0 +1,300 @@
+//! Editor features computed from a document's source
+//!
+//! Everything here works on the lossless syntax tree, so it keeps working
+//! while a document has errors in it. Positions follow LSP: 0-based lines
+//! and columns counted in UTF-16 code units.
+
+use crate::cli;
+use crate::error::JingError;
+use crate::lexer::{Span, TokenType, KEYWORDS};
+use crate::registry::Registry;
+use crate::syntax::{self, NodeKind, SyntaxNode, SyntaxTree, TokenKind};
+
+/// A 0-based line and UTF-16 column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub character: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// An open text document
+#[derive(Debug, Clone)]
+pub struct Document {
+    pub text: String,
+    /// Byte offset where each line starts
+    line_starts: Vec<usize>,
+}
+
+impl Document {
+    pub fn new(text: impl Into<String>) -> Self {
+        let text = text.into();
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Document { text, line_starts }
+    }
+
+    /// Position of byte `offset`
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let start = self.line_starts[line];
+        Position {
+            line,
+            character: self.text[start..offset].encode_utf16().count(),
+        }
+    }
+
+    /// Byte offset of `position`, clamped to the end of its line
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(&start) = self.line_starts.get(position.line) else {
+            return self.text.len();
+        };
+        let mut units = 0;
+        for (i, c) in self.text[start..].char_indices() {
+            if units >= position.character || c == '\n' {
+                return start + i;
+            }
+            units += c.len_utf16();
+        }
+        self.text.len()
+    }
+
+    pub fn range(&self, span: Span) -> Range {
+        Range {
+            start: self.position(span.start),
+            end: self.position(span.end),
+        }
+    }
+
+    /// Range covering the text of a 1-based line, as used in errors
+    fn line_range(&self, line: usize) -> Range {
+        let index = line.saturating_sub(1).min(self.line_starts.len() - 1);
+        let start = self.line_starts[index];
+        let end = self.text[start..]
+            .find('\n')
+            .map_or(self.text.len(), |end| start + end);
+        self.range(Span::new(start, end.max(start)))
+    }
+
+    fn tree(&self) -> SyntaxTree {
+        syntax::parse(&self.text)
+    }
+}
+
+/// A problem reported by the lexer, parser or compiler
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub range: Range,
+    pub message: String,
+}
+
+/// Check a document the way `jing check` does
+///
+/// Compilation stops at the first error, so there is at most one
+/// diagnostic. Compile errors have no line and are reported on the first.
+pub fn diagnostics(document: &Document) -> Vec<Diagnostic> {
+    let err = match cli::compile(&document.text) {
+        Ok(_) => return Vec::new(),
+        Err(err) => err,
+    };
+    let (line, message) = match err {
+        JingError::LexError { message, line } | JingError::ParseError { message, line } => {
+            (line, message)
+        }
+        JingError::CompileError { message } => (1, message),
+        other => (1, other.to_string()),
+    };
+    vec![Diagnostic {
+        range: document.line_range(line),
+        message,
+    }]
+}
+
+/// Where the name under `offset` is declared
+///
+/// Parameters of the enclosing functions are searched first, then every
+/// `let`, `fn` and `import ... as` in the document, since `let` inside a
+/// function still declares a global.
+pub fn definition(document: &Document, offset: usize) -> Option<Span> {
+    let tree = document.tree();
+    let name = name_at(&tree.root, offset)?;
+
+    let mut enclosing: Vec<&SyntaxNode> = tree
+        .root
+        .descendants()
+        .into_iter()
+        .filter(|node| node.kind == NodeKind::FnStmt && node.span.contains(offset))
+        .collect();
+    enclosing.reverse();
+    let parameter = enclosing.iter().find_map(|function| {
+        function
+            .child_nodes()
+            .filter(|node| node.kind == NodeKind::ParamList)
+            .flat_map(|list| list.child_nodes())
+            .find_map(|param| declared_name(param).filter(|(found, _)| *found == name))
+    });
+
+    parameter
+        .or_else(|| {
+            declarations(&tree.root)
+                .into_iter()
+                .find(|(_, found, _)| *found == name)
+                .map(|(_, found, span)| (found, span))
+        })
+        .map(|(_, span)| span)
+}
+
+/// Markdown describing the name under `offset`
+///
+/// Functions declared in the document show their signature; builtins show
+/// their help text and accepted argument counts.
+pub fn hover(document: &Document, offset: usize, registry: &Registry) -> Option<String> {
+    let tree = document.tree();
+    let name = name_at(&tree.root, offset)?;
+
+    let function = tree.root.descendants().into_iter().find(|node| {
+        node.kind == NodeKind::FnStmt && declared_name(node).is_some_and(|(found, _)| found == name)
+    });
+    if let Some(function) = function {
+        let params = function
+            .child_nodes()
+            .find(|node| node.kind == NodeKind::ParamList)
+            .map_or_else(String::new, SyntaxNode::text);
+        return Some(format!("```jing\nfn {}{}\n```", name, params));
+    }
+
+    registry
+        .help(&name)
+        .map(|help| format!("```jing\n{}\n```\nBuiltin function", help))
+}
+
+/// What a completion item names
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Builtin,
+    Function,
+    Variable,
+    Keyword,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    /// Help text for builtins
+    pub detail: Option<String>,
+}
+
+/// Builtins from `registry`, names declared in the document and keywords
+pub fn completions(document: &Document, registry: &Registry) -> Vec<Completion> {
+    let mut items: Vec<Completion> = registry
+        .names()
+        .into_iter()
+        .map(|name| Completion {
+            detail: registry.help(&name),
+            label: name,
+            kind: CompletionKind::Builtin,
+        })
+        .collect();
+
+    for (kind, name, _) in declarations(&document.tree().root) {
+        if !items.iter().any(|item| item.label == name) {
+            let kind = match kind {
+                NodeKind::FnStmt => CompletionKind::Function,
+                _ => CompletionKind::Variable,
+            };
+            items.push(Completion {
+                label: name,
+                kind,
+                detail: None,
+            });
+        }
+    }
+
+    items.extend(KEYWORDS.iter().map(|(keyword, _)| Completion {
+        label: keyword.to_string(),
+        kind: CompletionKind::Keyword,
+        detail: None,
+    }));
+    items
+}
+
+/// A function declared in the document, with the functions nested in it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    /// The whole declaration
+    pub span: Span,
+    /// Just the name
+    pub name_span: Span,
+    pub children: Vec<Symbol>,
+}
+
+/// The document's functions as an outline
+pub fn document_symbols(document: &Document) -> Vec<Symbol> {
+    function_symbols(&document.tree().root)
+}
+
+fn function_symbols(node: &SyntaxNode) -> Vec<Symbol> {
+    let mut symbols = Vec::new();
+    for child in node.child_nodes() {
+        match declared_name(child) {
+            Some((name, name_span)) if child.kind == NodeKind::FnStmt => symbols.push(Symbol {
+                name,
+                span: child.span,
+                name_span,
+                children: function_symbols(child),
+            }),
+            _ => symbols.extend(function_symbols(child)),
+        }
+    }
+    symbols
+}
+
+/// Every `let`, `fn` and aliased `import` in source order
+fn declarations(root: &SyntaxNode) -> Vec<(NodeKind, String, Span)> {
+    root.descendants()
+        .into_iter()
+        .filter(|node| {
+            matches!(
+                node.kind,
+                NodeKind::LetStmt | NodeKind::FnStmt | NodeKind::ImportStmt
+            )
+        })
+        .filter_map(|node| declared_name(node).map(|(name, span)| (node.kind, name, span)))
+        .collect()
+}
+
+/// The name a `let`, `fn`, parameter or `import ... as` declares
+///
+/// It is the first identifier directly inside the node.
+fn declared_name(node: &SyntaxNode) -> Option<(String, Span)> {
+    if !matches!(
+        node.kind,
+        NodeKind::LetStmt | NodeKind::FnStmt | NodeKind::Param | NodeKind::ImportStmt
+    ) {
+        return None;
+    }
+    node.children.iter().find_map(|child| match child {
+        syntax::SyntaxElement::Token(token) => match &token.kind {
+            TokenKind::Token(TokenType::Identifier(name)) => Some((name.clone(), token.span)),
+            _ => None,
+        },
+        syntax::SyntaxElement::Node(_) => None,
+    })
+}
+
+/// The identifier under `offset`
+fn name_at(root: &SyntaxNode, offset: usize) -> Option<String> {
+    match &root.token_at(offset)?.kind {
+        TokenKind::Token(TokenType::Identifier(name)) => Some(name.clone()),
+        _ => None,
+    }
+}
@@ -0,0 +1,407 @@
+//! Language server behind `jing lsp`
+//!
+//! Speaks the Language Server Protocol as JSON-RPC over standard input and
+//! output. Documents are synced in full on every change and checked the way
+//! `jing check` does, and the server answers go-to-definition, hover,
+//! completion and document symbol requests from the concrete syntax tree.
+//!
+//! `Server::run` takes any reader and writer, so a session can be scripted
+//! in tests by writing framed messages into a buffer.
+
+pub mod analysis;
+pub mod json;
+
+use crate::registry::Registry;
+use analysis::{CompletionKind, Document, Position, Range, Symbol};
+use json::Json;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Read, Write};
+
+/// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Largest message body the server reads; longer ones are skipped
+pub const MAX_MESSAGE_LENGTH: usize = 64 * 1024 * 1024;
+
+/// LSP `SymbolKind.Function`
+const SYMBOL_FUNCTION: usize = 12;
+/// LSP `DiagnosticSeverity.Error`
+const SEVERITY_ERROR: usize = 1;
+
+/// State of one language server session
+#[derive(Debug, Default)]
+pub struct Server {
+    documents: HashMap<String, Document>,
+    registry: Registry,
+    shutdown: bool,
+    exit: bool,
+}
+
+impl Server {
+    /// A server offering the standard builtins for hover and completion
+    pub fn new() -> Self {
+        Server::with_registry(Registry::with_defaults())
+    }
+
+    /// A server offering the builtins in `registry`
+    pub fn with_registry(registry: Registry) -> Self {
+        Server {
+            registry,
+            ..Server::default()
+        }
+    }
+
+    /// Serve messages from `input` until `exit` or end of input
+    ///
+    /// Returns the process exit status: 0 when the client sent `shutdown`
+    /// before exiting, 1 otherwise, as the protocol asks.
+    pub fn run(&mut self, mut input: impl BufRead, mut output: impl Write) -> io::Result<i32> {
+        while !self.exit {
+            // A malformed message is answered, not fatal
+            let body = match read_message(&mut input) {
+                Ok(Some(body)) => Ok(body),
+                Ok(None) => break,
+                Err(err) if err.kind() == io::ErrorKind::InvalidData => Err(err.to_string()),
+                Err(err) => return Err(err),
+            };
+            let replies = match body.and_then(|body| Json::parse(&body)) {
+                Ok(message) => self.handle(&message),
+                Err(err) => vec![error_response(Json::Null, PARSE_ERROR, &err)],
+            };
+            for reply in replies {
+                write_message(&mut output, &reply)?;
+            }
+        }
+        Ok(if self.shutdown { 0 } else { 1 })
+    }
+
+    /// Handle one message, returning the responses and notifications to send
+    pub fn handle(&mut self, message: &Json) -> Vec<Json> {
+        let method = message.get("method").and_then(Json::as_str).unwrap_or("");
+        let params = message.get("params").unwrap_or(&Json::Null);
+        let Some(id) = message.get("id").cloned() else {
+            return self.notification(method, params);
+        };
+
+        if self.shutdown {
+            return vec![error_response(
+                id,
+                INVALID_REQUEST,
+                "Server is shutting down",
+            )];
+        }
+        let result = match method {
+            "initialize" => Ok(capabilities()),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Json::Null)
+            }
+            "textDocument/definition" => self.definition(params),
+            "textDocument/hover" => self.hover(params),
+            "textDocument/completion" => self.completion(params),
+            "textDocument/documentSymbol" => self.document_symbols(params),
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown method '{}'", method))),
+        };
+        match result {
+            Ok(result) => vec![Json::object([
+                ("jsonrpc", Json::from("2.0")),
+                ("id", id),
+                ("result", result),
+            ])],
+            Err((code, message)) => vec![error_response(id, code, &message)],
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: &Json) -> Vec<Json> {
+        let uri = params
+            .at(&["textDocument", "uri"])
+            .and_then(Json::as_str)
+            .map(str::to_string);
+
+        match (method, uri) {
+            ("textDocument/didOpen", Some(uri)) => {
+                let text = params
+                    .at(&["textDocument", "text"])
+                    .and_then(Json::as_str)
+                    .unwrap_or("");
+                self.documents.insert(uri.clone(), Document::new(text));
+                vec![self.publish_diagnostics(&uri)]
+            }
+            ("textDocument/didChange", Some(uri)) => {
+                // Full sync: the last change holds the whole new text
+                let text = params
+                    .get("contentChanges")
+                    .and_then(Json::as_array)
+                    .and_then(<[Json]>::last)
+                    .and_then(|change| change.get("text"))
+                    .and_then(Json::as_str);
+                match text {
+                    Some(text) => {
+                        self.documents.insert(uri.clone(), Document::new(text));
+                        vec![self.publish_diagnostics(&uri)]
+                    }
+                    None => Vec::new(),
+                }
+            }
+            ("textDocument/didClose", Some(uri)) => {
+                self.documents.remove(&uri);
+                vec![diagnostics_notification(&uri, Vec::new())]
+            }
+            ("exit", _) => {
+                self.exit = true;
+                Vec::new()
+            }
+            // `initialized`, `$/cancelRequest` and the like need no reply
+            _ => Vec::new(),
+        }
+    }
+
+    fn publish_diagnostics(&self, uri: &str) -> Json {
+        let document = &self.documents[uri];
+        let diagnostics = analysis::diagnostics(document)
+            .into_iter()
+            .map(|diagnostic| {
+                Json::object([
+                    ("range", range_json(diagnostic.range)),
+                    ("severity", Json::from(SEVERITY_ERROR)),
+                    ("source", Json::from("jing")),
+                    ("message", Json::from(diagnostic.message)),
+                ])
+            })
+            .collect();
+        diagnostics_notification(uri, diagnostics)
+    }
+
+    /// The open document named in `params` and the byte offset of its
+    /// `position`, if one is given
+    fn document_at<'a>(
+        &'a self,
+        params: &'a Json,
+    ) -> Result<(&'a str, &'a Document, usize), (i64, String)> {
+        let uri = params
+            .at(&["textDocument", "uri"])
+            .and_then(Json::as_str)
+            .ok_or_else(|| (INVALID_PARAMS, "Missing textDocument.uri".to_string()))?;
+        let document = self
+            .documents
+            .get(uri)
+            .ok_or_else(|| (INVALID_PARAMS, format!("Document '{}' is not open", uri)))?;
+        let position = Position {
+            line: params
+                .at(&["position", "line"])
+                .and_then(Json::as_usize)
+                .unwrap_or(0),
+            character: params
+                .at(&["position", "character"])
+                .and_then(Json::as_usize)
+                .unwrap_or(0),
+        };
+        Ok((uri, document, document.offset(position)))
+    }
+
+    fn definition(&self, params: &Json) -> Result<Json, (i64, String)> {
+        let (uri, document, offset) = self.document_at(params)?;
+        Ok(
+            analysis::definition(document, offset).map_or(Json::Null, |span| {
+                Json::object([
+                    ("uri", Json::from(uri)),
+                    ("range", range_json(document.range(span))),
+                ])
+            }),
+        )
+    }
+
+    fn hover(&self, params: &Json) -> Result<Json, (i64, String)> {
+        let (_, document, offset) = self.document_at(params)?;
+        Ok(
+            analysis::hover(document, offset, &self.registry).map_or(Json::Null, |text| {
+                Json::object([(
+                    "contents",
+                    Json::object([
+                        ("kind", Json::from("markdown")),
+                        ("value", Json::from(text)),
+                    ]),
+                )])
+            }),
+        )
+    }
+
+    fn completion(&self, params: &Json) -> Result<Json, (i64, String)> {
+        let (_, document, _) = self.document_at(params)?;
+        let items = analysis::completions(document, &self.registry)
+            .into_iter()
+            .map(|item| {
+                // LSP `CompletionItemKind` values
+                let kind: usize = match item.kind {
+                    CompletionKind::Builtin | CompletionKind::Function => 3,
+                    CompletionKind::Variable => 6,
+                    CompletionKind::Keyword => 14,
+                };
+                let mut entries = vec![
+                    ("label", Json::from(item.label)),
+                    ("kind", Json::from(kind)),
+                ];
+                if let Some(detail) = item.detail {
+                    entries.push(("detail", Json::from(detail)));
+                }
+                Json::object(entries)
+            })
+            .collect();
+        Ok(Json::Array(items))
+    }
+
+    fn document_symbols(&self, params: &Json) -> Result<Json, (i64, String)> {
+        let (_, document, _) = self.document_at(params)?;
+        Ok(Json::Array(
+            analysis::document_symbols(document)
+                .iter()
+                .map(|symbol| symbol_json(document, symbol))
+                .collect(),
+        ))
+    }
+}
+
+fn capabilities() -> Json {
+    Json::object([
+        (
+            "capabilities",
+            Json::object([
+                // Full document sync
+                ("textDocumentSync", Json::from(1usize)),
+                ("definitionProvider", Json::from(true)),
+                ("hoverProvider", Json::from(true)),
+                ("completionProvider", Json::object::<&str>([])),
+                ("documentSymbolProvider", Json::from(true)),
+            ]),
+        ),
+        (
+            "serverInfo",
+            Json::object([
+                ("name", Json::from("jing")),
+                ("version", Json::from(env!("CARGO_PKG_VERSION"))),
+            ]),
+        ),
+    ])
+}
+
+fn error_response(id: Json, code: i64, message: &str) -> Json {
+    Json::object([
+        ("jsonrpc", Json::from("2.0")),
+        ("id", id),
+        (
+            "error",
+            Json::object([("code", Json::from(code)), ("message", Json::from(message))]),
+        ),
+    ])
+}
+
+fn diagnostics_notification(uri: &str, diagnostics: Vec<Json>) -> Json {
+    Json::object([
+        ("jsonrpc", Json::from("2.0")),
+        ("method", Json::from("textDocument/publishDiagnostics")),
+        (
+            "params",
+            Json::object([
+                ("uri", Json::from(uri)),
+                ("diagnostics", Json::Array(diagnostics)),
+            ]),
+        ),
+    ])
+}
+
+fn position_json(position: Position) -> Json {
+    Json::object([
+        ("line", Json::from(position.line)),
+        ("character", Json::from(position.character)),
+    ])
+}
+
+fn range_json(range: Range) -> Json {
+    Json::object([
+        ("start", position_json(range.start)),
+        ("end", position_json(range.end)),
+    ])
+}
+
+fn symbol_json(document: &Document, symbol: &Symbol) -> Json {
+    Json::object([
+        ("name", Json::from(symbol.name.as_str())),
+        ("kind", Json::from(SYMBOL_FUNCTION)),
+        ("range", range_json(document.range(symbol.span))),
+        (
+            "selectionRange",
+            range_json(document.range(symbol.name_span)),
+        ),
+        (
+            "children",
+            Json::Array(
+                symbol
+                    .children
+                    .iter()
+                    .map(|child| symbol_json(document, child))
+                    .collect(),
+            ),
+        ),
+    ])
+}
+
+/// Read one `Content-Length` framed message body, or `None` at end of input
+///
+/// A body that is not UTF-8, ends early or is longer than
+/// `MAX_MESSAGE_LENGTH` is an `InvalidData` error. The input is left at the
+/// next message, so reading can go on.
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            if length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let length = length.unwrap_or(0);
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    if length > MAX_MESSAGE_LENGTH {
+        io::copy(&mut input.by_ref().take(length as u64), &mut io::sink())?;
+        return Err(invalid(format!(
+            "Message of {} bytes is larger than the limit of {} bytes",
+            length, MAX_MESSAGE_LENGTH
+        )));
+    }
+
+    // Grow the buffer as data arrives rather than trusting the header
+    let mut body = Vec::new();
+    input.by_ref().take(length as u64).read_to_end(&mut body)?;
+    if body.len() < length {
+        return Err(invalid(format!(
+            "Message ended after {} of {} bytes",
+            body.len(),
+            length
+        )));
+    }
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|err| invalid(format!("Message is not valid UTF-8: {}", err)))
+}
+
+/// Write `message` with a `Content-Length` header
+pub fn write_message(output: &mut impl Write, message: &Json) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
@@ -0,0 +1,114 @@
+//! Modules loaded with `import`.
+//!
+//! Each imported `.jing` file is compiled into its own `Chunk` and runs
+//! with its own globals, so modules cannot see or overwrite each other's
+//! names. Function values carry the index of the module that defined them,
+//! which lets the VM switch to the right chunk when they are called from
+//! elsewhere. A module's exports are collected into a map that the
+//! importing script binds to the module's name.
+//!
+//! The standard library in the repository's `std` directory is written in
+//! Jing and compiled into the binary; `import "std/strings";` loads it
+//! without touching the filesystem.
+
+use crate::compiler::Chunk;
+use crate::value::{Environment, Value};
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Environment variable listing extra directories to search for modules
+pub const JING_PATH: &str = "JING_PATH";
+
+/// Modules bundled with the interpreter, by import name
+pub const STD_MODULES: &[(&str, &str)] = &[
+    ("std/assert", include_str!("../std/assert.jing")),
+    ("std/math", include_str!("../std/math.jing")),
+    ("std/strings", include_str!("../std/strings.jing")),
+];
+
+/// A chunk of code together with the globals it runs in
+#[derive(Debug, Clone)]
+pub struct Module {
+    /// Source file (or `std/...` name of a bundled module), used as the
+    /// base for relative imports and as the cache key; `None` for code that
+    /// did not come from a file
+    pub path: Option<PathBuf>,
+    pub chunk: Chunk,
+    pub globals: Environment,
+    /// Exported values, set once the module has finished running
+    pub namespace: Option<Value>,
+}
+
+impl Module {
+    /// An empty module for the main script
+    pub fn main(path: Option<PathBuf>) -> Self {
+        Module {
+            path,
+            chunk: Chunk::new(),
+            globals: Environment::new(),
+            namespace: None,
+        }
+    }
+
+    /// Point the function values in `chunk` at the module with index `index`
+    ///
+    /// The compiler cannot know where a chunk will be loaded, so function
+    /// constants are emitted for module 0 and relocated here.
+    pub fn link(mut chunk: Chunk, index: usize) -> Chunk {
+        for constant in &mut chunk.constants {
+            if let Value::Function { module, .. } = constant {
+                *module = index;
+            }
+        }
+        chunk
+    }
+
+    /// Collect the values of the chunk's exported names into a map
+    pub fn exports(&self) -> Value {
+        Value::Map(
+            self.chunk
+                .exports
+                .iter()
+                .filter_map(|name| Some((name.clone(), self.globals.get(name).ok()?)))
+                .collect(),
+        )
+    }
+}
+
+/// Look up a bundled standard library module, returning its name and source
+///
+/// Both `std/strings` and `std/strings.jing` name the same module.
+pub fn bundled(spec: &str) -> Option<(&'static str, &'static str)> {
+    let name = spec.strip_suffix(".jing").unwrap_or(spec);
+    STD_MODULES
+        .iter()
+        .find(|(bundled, _)| *bundled == name)
+        .copied()
+}
+
+/// The paths `import "spec"` may refer to, in search order
+///
+/// The directory of the importing file is searched first (the current
+/// directory for code without a file), then each directory in
+/// `search_path`, the value of `JING_PATH`. A path without an extension
+/// also matches the same path with `.jing`.
+pub fn candidates(spec: &str, from: Option<&Path>, search_path: Option<&str>) -> Vec<PathBuf> {
+    let base = from
+        .and_then(Path::parent)
+        .map_or_else(PathBuf::new, Path::to_path_buf);
+    let search_path = search_path
+        .map(|paths| env::split_paths(paths).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    std::iter::once(base)
+        .chain(search_path)
+        .flat_map(|dir| {
+            let path = dir.join(spec);
+            let with_extension = path
+                .extension()
+                .is_none()
+                .then(|| path.with_extension("jing"));
+            std::iter::once(path).chain(with_extension)
+        })
+        .collect()
+}
@@ -1,5 +1,6 @@
 use crate::error::{JingError, JingResult};
 use crate::lexer::{Token, TokenType};
+use crate::syntax::{Event, NodeKind};
 
 /// Abstract Syntax Tree node types
 #[derive(Debug, Clone, PartialEq)]
@@ -11,6 +12,7 @@ pub enum Expr {
     Call(CallExpr),
     Logical(LogicalExpr),
     Assign(AssignExpr),
+    Get(GetExpr),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -19,6 +21,13 @@ pub struct AssignExpr {
     pub value: Box<Expr>,
 }
 
+/// Field access such as `strs.pad`, reading a key of a map or module
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetExpr {
+    pub object: Box<Expr>,
+    pub name: String,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct LiteralExpr {
     pub value: LiteralValue,
@@ -75,6 +84,14 @@ pub enum UnaryOperator {
 pub struct CallExpr {
     pub callee: Box<Expr>,
     pub args: Vec<Expr>,
+    /// Named arguments such as `port: 8080`, which follow the positional ones
+    pub named: Vec<NamedArg>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedArg {
+    pub name: String,
+    pub value: Expr,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -101,6 +118,8 @@ pub enum Stmt {
     Function(FunctionStmt),
     Return(ReturnStmt),
     Print(PrintStmt),
+    Import(ImportStmt),
+    Export(ExportStmt),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -135,10 +154,31 @@ pub struct WhileStmt {
 #[derive(Debug, Clone, PartialEq)]
 pub struct FunctionStmt {
     pub name: String,
-    pub params: Vec<String>,
+    pub params: Vec<Param>,
     pub body: Box<Stmt>,
 }
 
+/// A function parameter: `name`, `name = default` or `...name`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub name: String,
+    /// Expression evaluated at call time when the argument is omitted
+    pub default: Option<Expr>,
+    /// Collects the remaining arguments into a list
+    pub rest: bool,
+}
+
+impl Param {
+    /// A required parameter without a default value
+    pub fn required(name: impl Into<String>) -> Self {
+        Param {
+            name: name.into(),
+            default: None,
+            rest: false,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ReturnStmt {
     pub value: Option<Expr>,
@@ -149,15 +189,88 @@ pub struct PrintStmt {
     pub expr: Expr,
 }
 
+/// `import "path" as name;`, binding a module's exports to `name`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportStmt {
+    pub path: String,
+    /// Defaults to the file name without its extension
+    pub alias: String,
+}
+
+/// `export let ...` or `export fn ...`, making a top-level name importable
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportStmt {
+    pub declaration: Box<Stmt>,
+}
+
+/// First and last source line of a parsed statement or block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineSpan {
+    pub start: usize,
+    pub end: usize,
+}
+
 /// Parser for Jing
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    /// Spans of the statements in the program and in blocks, in the order
+    /// they start
+    statement_spans: Vec<LineSpan>,
+    /// Spans of `{ ... }` blocks, from `{` to `}`, in the order they start
+    block_spans: Vec<LineSpan>,
+    /// Node boundaries and consumed tokens, recorded only in CST mode
+    events: Option<Vec<Event>>,
+    /// First error that CST mode recovered from
+    recovered: Option<JingError>,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Parser { tokens, current: 0 }
+        // Statements end with semicolons, so line breaks only separate tokens
+        // and expressions may span several lines
+        let tokens = tokens
+            .into_iter()
+            .filter(|token| !matches!(token.token_type, TokenType::Newline))
+            .collect();
+        Parser {
+            tokens,
+            current: 0,
+            statement_spans: Vec::new(),
+            block_spans: Vec::new(),
+            events: None,
+            recovered: None,
+        }
+    }
+
+    /// Lines covered by each statement of the program or of a block
+    ///
+    /// Statements are listed in the order they start, so nested statements
+    /// follow the statement containing them. Single statements used as an
+    /// `if` or `while` body without braces are not included.
+    pub fn statement_spans(&self) -> &[LineSpan] {
+        &self.statement_spans
+    }
+
+    /// Lines covered by each block, in the order the blocks start
+    pub fn block_spans(&self) -> &[LineSpan] {
+        &self.block_spans
+    }
+
+    /// Parse a program, also recording the events `syntax::parse` needs
+    /// to build a concrete syntax tree
+    ///
+    /// A statement that fails to parse, in the program or in a block, is
+    /// wrapped in an `Error` node and parsing resumes after it, so the
+    /// events always cover every token. The first error is returned.
+    pub(crate) fn parse_events(&mut self) -> (JingResult<Vec<Stmt>>, Vec<Event>) {
+        self.events = Some(Vec::new());
+        let result = self.parse();
+        let result = match self.recovered.take() {
+            Some(err) => Err(err),
+            None => result,
+        };
+        (result, self.events.take().unwrap_or_default())
     }
 
     /// Parse a program (list of statements)
@@ -170,21 +283,118 @@ impl Parser {
                 continue;
             }
 
-            statements.push(self.declaration()?);
+            statements.extend(self.recovering_declaration()?);
         }
 
         Ok(statements)
     }
 
-    /// Parse a declaration
+    /// Parse a declaration, recovering from an error in CST mode
+    ///
+    /// In CST mode a declaration that fails is wrapped in an `Error` node
+    /// reaching past the next `;`, or up to a `}` that may close the
+    /// enclosing block, and `None` is returned so parsing can go on.
+    fn recovering_declaration(&mut self) -> JingResult<Option<Stmt>> {
+        let checkpoint = self.checkpoint();
+        let start = self.current;
+        match self.declaration() {
+            Ok(statement) => Ok(Some(statement)),
+            Err(err) if self.events.is_some() => {
+                if self.current == start {
+                    // Always move on, even past a stray `}` at the top level
+                    self.advance();
+                } else {
+                    while !self.is_at_end() && !self.check(&TokenType::RightBrace) {
+                        if self.advance().token_type == TokenType::Semicolon {
+                            break;
+                        }
+                    }
+                }
+                self.finish_node_at(checkpoint, NodeKind::Error);
+                self.recovered.get_or_insert(err);
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Parse a declaration, recording the lines it covers
     fn declaration(&mut self) -> JingResult<Stmt> {
-        if self.match_token(&TokenType::Let) {
-            self.let_declaration()
+        let index = self.statement_spans.len();
+        let start = self.current_line();
+        self.statement_spans.push(LineSpan { start, end: start });
+
+        let statement = self.declaration_kind()?;
+        self.statement_spans[index].end = self.previous().line;
+        Ok(statement)
+    }
+
+    /// Parse the declaration or statement starting at the current token
+    fn declaration_kind(&mut self) -> JingResult<Stmt> {
+        let checkpoint = self.checkpoint();
+        let declaration = if self.match_token(&TokenType::Import) {
+            self.import_declaration()?
+        } else if self.match_token(&TokenType::Export) {
+            self.export_declaration()?
+        } else if self.match_token(&TokenType::Let) {
+            self.let_declaration()?
         } else if self.match_token(&TokenType::Fn) {
-            self.function_declaration()
+            self.function_declaration()?
         } else {
-            self.statement()
-        }
+            return self.statement();
+        };
+
+        self.finish_node_at(checkpoint, statement_node(&declaration));
+        Ok(declaration)
+    }
+
+    /// Parse an import declaration
+    fn import_declaration(&mut self) -> JingResult<Stmt> {
+        let path = match &self.peek().token_type {
+            TokenType::String(path) => path.clone(),
+            _ => {
+                return Err(JingError::parse_error(
+                    "Expected module path string after 'import'",
+                    self.current_line(),
+                ))
+            }
+        };
+        self.advance();
+
+        let alias = if self.match_token(&TokenType::As) {
+            self.consume_identifier("Expected module name after 'as'")?
+        } else {
+            default_alias(&path).ok_or_else(|| {
+                JingError::parse_error(
+                    format!("Cannot name module '{}'; use 'as name'", path),
+                    self.current_line(),
+                )
+            })?
+        };
+
+        self.consume(&TokenType::Semicolon, "Expected ';' after import")?;
+
+        Ok(Stmt::Import(ImportStmt { path, alias }))
+    }
+
+    /// Parse an exported let or function declaration
+    fn export_declaration(&mut self) -> JingResult<Stmt> {
+        let checkpoint = self.checkpoint();
+        let declaration = if self.match_token(&TokenType::Let) {
+            self.let_declaration()?
+        } else if self.match_token(&TokenType::Fn) {
+            self.function_declaration()?
+        } else {
+            return Err(JingError::parse_error(
+                "Expected 'let' or 'fn' after 'export'",
+                self.current_line(),
+            ));
+        };
+        self.finish_node_at(checkpoint, statement_node(&declaration));
+
+        Ok(Stmt::Export(ExportStmt {
+            declaration: Box::new(declaration),
+        }))
     }
 
     /// Parse a let declaration
@@ -207,12 +417,58 @@ impl Parser {
     fn function_declaration(&mut self) -> JingResult<Stmt> {
         let name = self.consume_identifier("Expected function name")?;
 
+        let param_list = self.checkpoint();
         self.consume(&TokenType::LeftParen, "Expected '(' after function name")?;
 
-        let mut params = Vec::new();
+        let mut params: Vec<Param> = Vec::new();
         if !self.check(&TokenType::RightParen) {
             loop {
-                params.push(self.consume_identifier("Expected parameter name")?);
+                let param = self.checkpoint();
+                if params.last().is_some_and(|param| param.rest) {
+                    return Err(JingError::parse_error(
+                        "Rest parameter must be the last parameter",
+                        self.current_line(),
+                    ));
+                }
+
+                let rest = self.match_token(&TokenType::Ellipsis);
+                let name = self.consume_identifier("Expected parameter name")?;
+                if params.iter().any(|param| param.name == name) {
+                    return Err(JingError::parse_error(
+                        format!("Duplicate parameter '{}'", name),
+                        self.current_line(),
+                    ));
+                }
+
+                let default = if self.match_token(&TokenType::Equal) {
+                    if rest {
+                        return Err(JingError::parse_error(
+                            "Rest parameter cannot have a default value",
+                            self.current_line(),
+                        ));
+                    }
+                    Some(self.expression()?)
+                } else {
+                    None
+                };
+
+                if default.is_none() && !rest && params.iter().any(|param| param.default.is_some())
+                {
+                    return Err(JingError::parse_error(
+                        format!(
+                            "Parameter '{}' without a default cannot follow parameters with defaults",
+                            name
+                        ),
+                        self.current_line(),
+                    ));
+                }
+
+                params.push(Param {
+                    name,
+                    default,
+                    rest,
+                });
+                self.finish_node_at(param, NodeKind::Param);
                 if !self.match_token(&TokenType::Comma) {
                     break;
                 }
@@ -220,6 +476,7 @@ impl Parser {
         }
 
         self.consume(&TokenType::RightParen, "Expected ')' after parameters")?;
+        self.finish_node_at(param_list, NodeKind::ParamList);
 
         let body = Box::new(self.block_statement()?);
 
@@ -228,19 +485,23 @@ impl Parser {
 
     /// Parse a statement
     fn statement(&mut self) -> JingResult<Stmt> {
-        if self.match_token(&TokenType::If) {
-            self.if_statement()
+        let checkpoint = self.checkpoint();
+        let statement = if self.match_token(&TokenType::If) {
+            self.if_statement()?
         } else if self.match_token(&TokenType::While) {
-            self.while_statement()
+            self.while_statement()?
         } else if self.match_token(&TokenType::Return) {
-            self.return_statement()
+            self.return_statement()?
         } else if self.match_token(&TokenType::LeftBrace) {
-            Ok(Stmt::Block(BlockStmt {
+            Stmt::Block(BlockStmt {
                 statements: self.block()?,
-            }))
+            })
         } else {
-            self.expression_statement()
-        }
+            self.expression_statement()?
+        };
+
+        self.finish_node_at(checkpoint, statement_node(&statement));
+        Ok(statement)
     }
 
     /// Parse an if statement
@@ -284,23 +545,30 @@ impl Parser {
 
     /// Parse a block statement
     fn block_statement(&mut self) -> JingResult<Stmt> {
+        let checkpoint = self.checkpoint();
         self.consume(&TokenType::LeftBrace, "Expected '{'")?;
         let statements = self.block()?;
+        self.finish_node_at(checkpoint, NodeKind::Block);
         Ok(Stmt::Block(BlockStmt { statements }))
     }
 
-    /// Parse statements inside a block
+    /// Parse statements inside a block, after its opening brace
     fn block(&mut self) -> JingResult<Vec<Stmt>> {
+        let index = self.block_spans.len();
+        let start = self.previous().line;
+        self.block_spans.push(LineSpan { start, end: start });
+
         let mut statements = Vec::new();
 
         while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
             if self.match_token(&TokenType::Newline) {
                 continue;
             }
-            statements.push(self.declaration()?);
+            statements.extend(self.recovering_declaration()?);
         }
 
         self.consume(&TokenType::RightBrace, "Expected '}' after block")?;
+        self.block_spans[index].end = self.previous().line;
         Ok(statements)
     }
 
@@ -331,10 +599,12 @@ impl Parser {
 
     /// Parse assignment expressions
     fn assignment(&mut self) -> JingResult<Expr> {
+        let checkpoint = self.checkpoint();
         let expr = self.logical_or()?;
 
         if self.match_token(&TokenType::Equal) {
             let value = self.assignment()?;
+            self.finish_node_at(checkpoint, NodeKind::AssignExpr);
 
             if let Expr::Variable(var) = expr {
                 return Ok(Expr::Assign(AssignExpr {
@@ -351,10 +621,12 @@ impl Parser {
 
     /// Parse logical OR
     fn logical_or(&mut self) -> JingResult<Expr> {
+        let checkpoint = self.checkpoint();
         let mut expr = self.logical_and()?;
 
         while self.match_token(&TokenType::Or) {
             let right = self.logical_and()?;
+            self.finish_node_at(checkpoint, NodeKind::LogicalExpr);
             expr = Expr::Logical(LogicalExpr {
                 left: Box::new(expr),
                 operator: LogicalOperator::Or,
@@ -367,10 +639,12 @@ impl Parser {
 
     /// Parse logical AND
     fn logical_and(&mut self) -> JingResult<Expr> {
+        let checkpoint = self.checkpoint();
         let mut expr = self.equality()?;
 
         while self.match_token(&TokenType::And) {
             let right = self.equality()?;
+            self.finish_node_at(checkpoint, NodeKind::LogicalExpr);
             expr = Expr::Logical(LogicalExpr {
                 left: Box::new(expr),
                 operator: LogicalOperator::And,
@@ -383,10 +657,12 @@ impl Parser {
 
     /// Parse equality operations
     fn equality(&mut self) -> JingResult<Expr> {
+        let checkpoint = self.checkpoint();
         let mut expr = self.comparison()?;
 
         while let Some(operator) = self.match_equality_operator() {
             let right = self.comparison()?;
+            self.finish_node_at(checkpoint, NodeKind::BinaryExpr);
             expr = Expr::Binary(BinaryExpr {
                 left: Box::new(expr),
                 operator,
@@ -399,10 +675,12 @@ impl Parser {
 
     /// Parse comparison operations
     fn comparison(&mut self) -> JingResult<Expr> {
+        let checkpoint = self.checkpoint();
         let mut expr = self.term()?;
 
         while let Some(operator) = self.match_comparison_operator() {
             let right = self.term()?;
+            self.finish_node_at(checkpoint, NodeKind::BinaryExpr);
             expr = Expr::Binary(BinaryExpr {
                 left: Box::new(expr),
                 operator,
@@ -415,10 +693,12 @@ impl Parser {
 
     /// Parse addition and subtraction
     fn term(&mut self) -> JingResult<Expr> {
+        let checkpoint = self.checkpoint();
         let mut expr = self.factor()?;
 
         while let Some(operator) = self.match_term_operator() {
             let right = self.factor()?;
+            self.finish_node_at(checkpoint, NodeKind::BinaryExpr);
             expr = Expr::Binary(BinaryExpr {
                 left: Box::new(expr),
                 operator,
@@ -431,10 +711,12 @@ impl Parser {
 
     /// Parse multiplication, division, and modulo
     fn factor(&mut self) -> JingResult<Expr> {
+        let checkpoint = self.checkpoint();
         let mut expr = self.unary()?;
 
         while let Some(operator) = self.match_factor_operator() {
             let right = self.unary()?;
+            self.finish_node_at(checkpoint, NodeKind::BinaryExpr);
             expr = Expr::Binary(BinaryExpr {
                 left: Box::new(expr),
                 operator,
@@ -447,8 +729,10 @@ impl Parser {
 
     /// Parse unary operations
     fn unary(&mut self) -> JingResult<Expr> {
+        let checkpoint = self.checkpoint();
         if let Some(operator) = self.match_unary_operator() {
             let expr = self.unary()?;
+            self.finish_node_at(checkpoint, NodeKind::UnaryExpr);
             return Ok(Expr::Unary(UnaryExpr {
                 operator,
                 operand: Box::new(expr),
@@ -460,14 +744,44 @@ impl Parser {
 
     /// Parse function calls
     fn call(&mut self) -> JingResult<Expr> {
+        let checkpoint = self.checkpoint();
         let mut expr = self.primary()?;
 
-        while self.match_token(&TokenType::LeftParen) {
+        loop {
+            let arg_list = self.checkpoint();
+            if self.match_token(&TokenType::Dot) {
+                let name = self.consume_identifier("Expected field name after '.'")?;
+                self.finish_node_at(checkpoint, NodeKind::GetExpr);
+                expr = Expr::Get(GetExpr {
+                    object: Box::new(expr),
+                    name,
+                });
+                continue;
+            }
+            if !self.match_token(&TokenType::LeftParen) {
+                break;
+            }
+
             let mut args = Vec::new();
+            let mut named = Vec::new();
 
             if !self.check(&TokenType::RightParen) {
                 loop {
-                    args.push(self.expression()?);
+                    let arg = self.checkpoint();
+                    if let Some(name) = self.named_argument()? {
+                        named.push(NamedArg {
+                            name,
+                            value: self.expression()?,
+                        });
+                        self.finish_node_at(arg, NodeKind::NamedArg);
+                    } else if named.is_empty() {
+                        args.push(self.expression()?);
+                    } else {
+                        return Err(JingError::parse_error(
+                            "Positional argument cannot follow named arguments",
+                            self.current_line(),
+                        ));
+                    }
                     if !self.match_token(&TokenType::Comma) {
                         break;
                     }
@@ -475,10 +789,13 @@ impl Parser {
             }
 
             self.consume(&TokenType::RightParen, "Expected ')' after arguments")?;
+            self.finish_node_at(arg_list, NodeKind::ArgList);
+            self.finish_node_at(checkpoint, NodeKind::CallExpr);
 
             expr = Expr::Call(CallExpr {
                 callee: Box::new(expr),
                 args,
+                named,
             });
         }
 
@@ -487,6 +804,21 @@ impl Parser {
 
     /// Parse primary expressions
     fn primary(&mut self) -> JingResult<Expr> {
+        let checkpoint = self.checkpoint();
+        let grouped = self.check(&TokenType::LeftParen);
+        let expr = self.primary_kind()?;
+
+        let node = match expr {
+            _ if grouped => NodeKind::ParenExpr,
+            Expr::Variable(_) => NodeKind::NameRef,
+            _ => NodeKind::Literal,
+        };
+        self.finish_node_at(checkpoint, node);
+        Ok(expr)
+    }
+
+    /// Parse a literal, variable or parenthesized expression
+    fn primary_kind(&mut self) -> JingResult<Expr> {
         if self.match_token(&TokenType::True) {
             return Ok(Expr::Literal(LiteralExpr {
                 value: LiteralValue::Bool(true),
@@ -597,6 +929,40 @@ impl Parser {
     }
 
     // Utility methods
+    /// Consume `name:` at the start of a named argument, returning the name
+    fn named_argument(&mut self) -> JingResult<Option<String>> {
+        let is_named = matches!(self.peek().token_type, TokenType::Identifier(_))
+            && matches!(
+                self.tokens
+                    .get(self.current + 1)
+                    .map(|token| &token.token_type),
+                Some(TokenType::Colon)
+            );
+        if !is_named {
+            return Ok(None);
+        }
+
+        let name = self.consume_identifier("Expected argument name")?;
+        self.consume(&TokenType::Colon, "Expected ':' after argument name")?;
+        Ok(Some(name))
+    }
+
+    /// Position in the CST events where a node may later be started
+    fn checkpoint(&self) -> usize {
+        self.events.as_ref().map_or(0, Vec::len)
+    }
+
+    /// Wrap everything recorded since `checkpoint` in a node of `kind`
+    ///
+    /// Nodes are only known once parsed (`a + b` is a binary expression
+    /// only after the `+`), so the start is inserted after the fact.
+    fn finish_node_at(&mut self, checkpoint: usize, kind: NodeKind) {
+        if let Some(events) = &mut self.events {
+            events.insert(checkpoint, Event::Start(kind));
+            events.push(Event::Finish);
+        }
+    }
+
     fn match_token(&mut self, token_type: &TokenType) -> bool {
         if self.check_token_type(token_type) {
             self.advance();
@@ -634,11 +1000,15 @@ impl Parser {
     fn advance(&mut self) -> Token {
         if !self.is_at_end() {
             self.current += 1;
+            if let Some(events) = &mut self.events {
+                events.push(Event::Token);
+            }
         }
         self.previous()
     }
 
-    fn is_at_end(&self) -> bool {
+    /// Whether every token before the end of input has been consumed
+    pub(crate) fn is_at_end(&self) -> bool {
         matches!(self.peek().token_type, TokenType::Eof)
     }
 
@@ -681,6 +1051,35 @@ impl Parser {
     }
 }
 
+/// CST node kind for a statement
+fn statement_node(statement: &Stmt) -> NodeKind {
+    match statement {
+        Stmt::Expression(_) | Stmt::Print(_) => NodeKind::ExprStmt,
+        Stmt::Let(_) => NodeKind::LetStmt,
+        Stmt::Block(_) => NodeKind::Block,
+        Stmt::If(_) => NodeKind::IfStmt,
+        Stmt::While(_) => NodeKind::WhileStmt,
+        Stmt::Function(_) => NodeKind::FnStmt,
+        Stmt::Return(_) => NodeKind::ReturnStmt,
+        Stmt::Import(_) => NodeKind::ImportStmt,
+        Stmt::Export(_) => NodeKind::ExportStmt,
+    }
+}
+
+/// Name for `import "path"` without `as`: the file name minus its extension
+pub(crate) fn default_alias(path: &str) -> Option<String> {
+    let stem = std::path::Path::new(path).file_stem()?.to_str()?;
+    let mut chars = stem.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    let keyword = crate::lexer::KEYWORDS
+        .iter()
+        .any(|(keyword, _)| *keyword == stem);
+    (valid && !keyword).then(|| stem.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
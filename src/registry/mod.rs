@@ -1,44 +1,97 @@
-//! Central registry system for managing language extensions.
+//! Registry of builtin functions available to scripts.
 //!
-//! This module provides a simple registry for builtin functions
-//! that allows easy extension without modifying core files.
+//! Each `VM` owns its own `Registry`, so two VMs in one process can
+//! expose different builtins without interfering with each other.
+//! `Registry::with_defaults()` provides the standard set of builtins.
 
+use crate::builtins::native::{IntoNativeFunction, NativeFunction};
+use crate::error::{JingError, JingResult};
 use crate::features::BuiltinFunction;
+use crate::value::Value;
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 
-/// Simple global storage for builtin functions
-static BUILTIN_FUNCTIONS: Mutex<Option<HashMap<String, Arc<dyn BuiltinFunction>>>> =
-    Mutex::new(None);
+/// A set of builtin functions, keyed by name
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    functions: HashMap<String, Arc<dyn BuiltinFunction>>,
+}
 
-/// Initialize the builtin functions storage
-fn init_builtins_storage() {
-    let mut storage = BUILTIN_FUNCTIONS.lock().unwrap();
-    if storage.is_none() {
-        *storage = Some(HashMap::new());
+impl Registry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Registry {
+            functions: HashMap::new(),
+        }
     }
-}
 
-/// Register a builtin function globally
-pub fn register_builtin(function: Arc<dyn BuiltinFunction>) {
-    init_builtins_storage();
-    let mut storage = BUILTIN_FUNCTIONS.lock().unwrap();
-    let functions = storage.as_mut().unwrap();
-    functions.insert(function.name().to_string(), function);
-}
+    /// Create a registry containing all standard builtin functions
+    pub fn with_defaults() -> Self {
+        let mut registry = Registry::new();
+        crate::builtins::register_defaults(&mut registry);
+        registry
+    }
 
-/// Get a builtin function by name
-pub fn get_builtin(name: &str) -> Option<Arc<dyn BuiltinFunction>> {
-    init_builtins_storage();
-    let storage = BUILTIN_FUNCTIONS.lock().unwrap();
-    let functions = storage.as_ref().unwrap();
-    functions.get(name).cloned()
-}
+    /// Register a builtin function, replacing any function with the same name
+    pub fn register(&mut self, function: Arc<dyn BuiltinFunction>) {
+        self.functions.insert(function.name().to_string(), function);
+    }
+
+    /// Register a Rust closure as a builtin function
+    ///
+    /// Arguments are converted with `FromValue` and the result with
+    /// `IntoValue`, so `|a: f64, b: f64| a + b` becomes a two-argument
+    /// builtin that reports a type error when given non-numbers.
+    pub fn register_fn<Args, F: IntoNativeFunction<Args>>(&mut self, name: &str, function: F) {
+        self.register(Arc::new(NativeFunction::new(name, function)));
+    }
+
+    /// Remove a builtin function by name
+    pub fn unregister(&mut self, name: &str) -> Option<Arc<dyn BuiltinFunction>> {
+        self.functions.remove(name)
+    }
 
-/// Get all builtin function names
-pub fn builtin_names() -> Vec<String> {
-    init_builtins_storage();
-    let storage = BUILTIN_FUNCTIONS.lock().unwrap();
-    let functions = storage.as_ref().unwrap();
-    functions.keys().cloned().collect()
+    /// Get a builtin function by name
+    pub fn get(&self, name: &str) -> Option<Arc<dyn BuiltinFunction>> {
+        self.functions.get(name).cloned()
+    }
+
+    /// Check if a function name is a registered builtin
+    pub fn contains(&self, name: &str) -> bool {
+        self.functions.contains_key(name)
+    }
+
+    /// Get all builtin function names, sorted alphabetically
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.functions.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Get the help text for a builtin, including the arguments it accepts
+    pub fn help(&self, name: &str) -> Option<String> {
+        self.get(name)
+            .map(|builtin| format!("{} (takes {})", builtin.help(), builtin.arity().describe()))
+    }
+
+    /// Call a builtin function by name
+    pub fn call(&self, name: &str, args: Vec<Value>) -> JingResult<Value> {
+        match self.get(name) {
+            Some(builtin) => {
+                if !builtin.arity().accepts(args.len()) {
+                    return Err(JingError::runtime_error(format!(
+                        "Function '{}' expects {} arguments, got {}",
+                        name,
+                        builtin.arity(),
+                        args.len()
+                    )));
+                }
+                builtin.call(args)
+            }
+            None => Err(JingError::runtime_error(format!(
+                "Unknown builtin function: {}",
+                name
+            ))),
+        }
+    }
 }
@@ -0,0 +1,122 @@
+//! REPL meta-commands such as `:help` and `:load`
+
+use super::REPL;
+use crate::compiler::Chunk;
+use crate::error::{JingError, JingResult};
+use crate::value::Value;
+use std::fs;
+use std::time::Instant;
+
+/// Commands listed by `:help`
+const COMMANDS: &[(&str, &str)] = &[
+    (
+        ":help [name]",
+        "Show help for a builtin, or list all builtins",
+    ),
+    (":load <file>", "Run a script in the current session"),
+    (":reset", "Clear all variables and functions"),
+    (":vars", "List global variables and functions"),
+    (":type <expr>", "Show the type of an expression's value"),
+    (
+        ":time <expr>",
+        "Evaluate an expression and show how long it took",
+    ),
+];
+
+impl REPL {
+    /// Run a REPL command such as `:help print`, returning the text to show
+    pub fn command(&mut self, input: &str) -> JingResult<String> {
+        let input = input.trim();
+        let (name, argument) = match input.split_once(char::is_whitespace) {
+            Some((name, argument)) => (name, argument.trim()),
+            None => (input, ""),
+        };
+
+        match name {
+            ":help" => self.help(argument),
+            ":load" => self.load(argument),
+            ":reset" => {
+                self.vm.reset();
+                self.chunk = Chunk::new();
+                Ok("Session reset".to_string())
+            }
+            ":vars" => Ok(self.vars()),
+            ":type" => {
+                let value = self.evaluate(required(name, argument)?)?;
+                Ok(value.type_name().to_string())
+            }
+            ":time" => {
+                let source = required(name, argument)?;
+                let start = Instant::now();
+                let value = self.evaluate(source)?;
+                let elapsed = start.elapsed();
+                Ok(match value {
+                    Value::Nil => format!("Took {:?}", elapsed),
+                    value => format!("{}\nTook {:?}", value.repr(), elapsed),
+                })
+            }
+            _ => Err(JingError::runtime_error(format!(
+                "Unknown command '{}' (type :help for a list)",
+                name
+            ))),
+        }
+    }
+
+    fn help(&self, name: &str) -> JingResult<String> {
+        let registry = self.vm.registry();
+        if !name.is_empty() {
+            return registry.help(name).ok_or_else(|| {
+                JingError::runtime_error(format!("No builtin function named '{}'", name))
+            });
+        }
+
+        let mut lines = vec!["Commands:".to_string()];
+        for (usage, description) in COMMANDS {
+            lines.push(format!("  {:<14} {}", usage, description));
+        }
+        lines.push(String::new());
+        lines.push("Builtin functions:".to_string());
+        for name in registry.names() {
+            if let Some(builtin) = registry.get(&name) {
+                lines.push(format!("  {}", builtin.help()));
+            }
+        }
+        Ok(lines.join("\n"))
+    }
+
+    fn load(&mut self, path: &str) -> JingResult<String> {
+        let source = fs::read_to_string(required(":load", path)?).map_err(|err| {
+            JingError::io_error(format!("Could not read file '{}': {}", path, err))
+        })?;
+        self.evaluate(&source)?;
+        Ok(format!("Loaded {}", path))
+    }
+
+    fn vars(&self) -> String {
+        let names = self.vm.global_names();
+        if names.is_empty() {
+            return "No variables defined".to_string();
+        }
+
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let value = self.vm.get_global(&name)?;
+                Some(format!("{} = {}", name, value.repr()))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Check that a command was given its argument
+fn required<'a>(command: &str, argument: &'a str) -> JingResult<&'a str> {
+    if argument.is_empty() {
+        Err(JingError::runtime_error(format!(
+            "{} needs an argument",
+            command
+        )))
+    } else {
+        Ok(argument)
+    }
+}
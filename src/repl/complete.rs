@@ -0,0 +1,64 @@
+//! Tab completion of identifiers in the REPL
+
+use super::editor::{Completer, Completion};
+use super::REPL;
+use crate::lexer::KEYWORDS;
+
+/// Check if `c` can appear in an identifier
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+impl REPL {
+    /// Names that can be completed: keywords, globals, functions and builtins
+    pub fn completion_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = KEYWORDS
+            .iter()
+            .map(|(keyword, _)| keyword.to_string())
+            .chain(self.vm.global_names())
+            .chain(self.chunk.functions.keys().cloned())
+            .chain(self.vm.registry().names())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+impl Completer for REPL {
+    fn complete(&self, line: &str, cursor: usize) -> Completion {
+        let chars: Vec<char> = line.chars().collect();
+        let cursor = cursor.min(chars.len());
+        let start = chars[..cursor]
+            .iter()
+            .rposition(|c| !is_identifier_char(*c))
+            .map_or(0, |index| index + 1);
+        let word: String = chars[start..cursor].iter().collect();
+
+        // Identifiers cannot start with a digit, and an empty word would list everything
+        if word.is_empty() || word.starts_with(|c: char| c.is_ascii_digit()) {
+            return Completion {
+                start,
+                ..Completion::default()
+            };
+        }
+
+        let candidates: Vec<String> = self
+            .completion_names()
+            .into_iter()
+            .filter(|name| name.starts_with(&word))
+            .collect();
+
+        // Show the accepted arguments and help for a single builtin
+        let hint = match candidates.as_slice() {
+            [only] => self.vm.registry().help(only),
+            _ => None,
+        };
+
+        Completion {
+            start,
+            candidates,
+            hint,
+        }
+    }
+}
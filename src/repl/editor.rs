@@ -0,0 +1,493 @@
+//! Minimal line editor for the REPL
+//!
+//! Supports cursor movement, in-line editing and history navigation using
+//! ANSI escape sequences. Raw mode is toggled with `stty`, so no platform
+//! bindings are needed; when stdin is not a terminal, lines are read
+//! unedited.
+
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Maximum number of entries kept in the history file
+const MAX_HISTORY: usize = 1000;
+
+/// Outcome of reading one line
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReadResult {
+    /// A complete line, without the trailing newline
+    Line(String),
+    /// The user pressed Ctrl-C
+    Interrupted,
+    /// End of input (Ctrl-D on an empty line)
+    Eof,
+}
+
+/// A decoded key press
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Enter,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    /// Ctrl-U: delete everything before the cursor
+    KillStart,
+    /// Ctrl-K: delete everything from the cursor on
+    KillEnd,
+    Tab,
+    Interrupt,
+    Eof,
+    /// A key the editor does not handle
+    Ignored,
+}
+
+/// Read a single key from a raw-mode byte stream
+pub fn read_key(input: &mut impl Read) -> io::Result<Key> {
+    let byte = match read_byte(input)? {
+        Some(byte) => byte,
+        None => return Ok(Key::Eof),
+    };
+
+    let key = match byte {
+        b'\r' | b'\n' => Key::Enter,
+        0x7f | 0x08 => Key::Backspace,
+        0x01 => Key::Home,
+        0x05 => Key::End,
+        0x02 => Key::Left,
+        0x06 => Key::Right,
+        0x10 => Key::Up,
+        0x0e => Key::Down,
+        0x15 => Key::KillStart,
+        0x0b => Key::KillEnd,
+        b'\t' => Key::Tab,
+        0x03 => Key::Interrupt,
+        0x04 => Key::Eof,
+        0x1b => read_escape(input)?,
+        byte if byte < 0x20 => Key::Ignored,
+        byte => read_char(input, byte)?,
+    };
+    Ok(key)
+}
+
+fn read_byte(input: &mut impl Read) -> io::Result<Option<u8>> {
+    let mut buf = [0u8; 1];
+    match input.read(&mut buf)? {
+        0 => Ok(None),
+        _ => Ok(Some(buf[0])),
+    }
+}
+
+/// Decode the rest of an escape sequence such as `ESC [ A`
+fn read_escape(input: &mut impl Read) -> io::Result<Key> {
+    let intro = read_byte(input)?;
+    if !matches!(intro, Some(b'[') | Some(b'O')) {
+        return Ok(Key::Ignored);
+    }
+
+    let key = match read_byte(input)? {
+        Some(b'A') => Key::Up,
+        Some(b'B') => Key::Down,
+        Some(b'C') => Key::Right,
+        Some(b'D') => Key::Left,
+        Some(b'H') => Key::Home,
+        Some(b'F') => Key::End,
+        Some(digit @ b'0'..=b'9') => {
+            // Sequences like `ESC [ 3 ~`, or `ESC [ 1 ; 5 C` for modified
+            // keys, which end at any final byte
+            let mut code = vec![digit];
+            let last = loop {
+                match read_byte(input)? {
+                    Some(byte @ 0x40..=0x7e) => break Some(byte),
+                    None => break None,
+                    Some(byte) => code.push(byte),
+                }
+            };
+            match (code.as_slice(), last) {
+                (b"1" | b"7", Some(b'~')) => Key::Home,
+                (b"4" | b"8", Some(b'~')) => Key::End,
+                (b"3", Some(b'~')) => Key::Delete,
+                _ => Key::Ignored,
+            }
+        }
+        _ => Key::Ignored,
+    };
+    Ok(key)
+}
+
+/// Decode a UTF-8 character whose first byte is `first`
+fn read_char(input: &mut impl Read, first: u8) -> io::Result<Key> {
+    let len = match first {
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => 1,
+    };
+
+    let mut bytes = vec![first];
+    for _ in 1..len {
+        match read_byte(input)? {
+            Some(byte) => bytes.push(byte),
+            None => break,
+        }
+    }
+
+    Ok(std::str::from_utf8(&bytes)
+        .ok()
+        .and_then(|s| s.chars().next())
+        .map_or(Key::Ignored, Key::Char))
+}
+
+/// Candidate completions for the word ending at the cursor
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Completion {
+    /// Char index where the word being completed starts
+    pub start: usize,
+    /// Possible replacements for the word, sorted
+    pub candidates: Vec<String>,
+    /// Extra information to show for a single candidate, such as help text
+    pub hint: Option<String>,
+}
+
+/// Source of completions for the line editor
+pub trait Completer {
+    /// Complete the word before `cursor` (a char index) in `line`
+    fn complete(&self, line: &str, cursor: usize) -> Completion;
+}
+
+/// Completer that never suggests anything
+pub struct NoCompletion;
+
+impl Completer for NoCompletion {
+    fn complete(&self, _line: &str, cursor: usize) -> Completion {
+        Completion {
+            start: cursor,
+            ..Completion::default()
+        }
+    }
+}
+
+/// Previously entered lines, optionally backed by a file
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    entries: Vec<String>,
+    path: Option<PathBuf>,
+}
+
+impl History {
+    /// Empty history that is not saved
+    pub fn new() -> Self {
+        History::default()
+    }
+
+    /// Load history from `path`; a missing or unreadable file starts empty
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let entries = fs::read_to_string(&path)
+            .map(|contents| contents.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+        History {
+            entries,
+            path: Some(path),
+        }
+    }
+
+    /// The default history file, `~/.jing_history`
+    pub fn default_path() -> Option<PathBuf> {
+        std::env::var_os("HOME").map(|home| Path::new(&home).join(".jing_history"))
+    }
+
+    /// Record a line, skipping blanks and immediate repeats
+    pub fn add(&mut self, line: &str) {
+        if line.trim().is_empty() || self.entries.last().is_some_and(|last| last == line) {
+            return;
+        }
+        self.entries.push(line.to_string());
+        if self.entries.len() > MAX_HISTORY {
+            let excess = self.entries.len() - MAX_HISTORY;
+            self.entries.drain(..excess);
+        }
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Write the history back to its file, if it has one
+    pub fn save(&self) -> io::Result<()> {
+        match &self.path {
+            Some(path) => {
+                let mut contents = self.entries.join("\n");
+                contents.push('\n');
+                fs::write(path, contents)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// The line being edited and the cursor position within it
+#[derive(Debug, Clone, Default)]
+pub struct LineBuffer {
+    chars: Vec<char>,
+    cursor: usize,
+    /// Position while browsing history, and the line typed before browsing
+    history_index: Option<usize>,
+    draft: Vec<char>,
+}
+
+/// What the editor should do after a key press
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Continue,
+    Done(ReadResult),
+}
+
+impl LineBuffer {
+    pub fn new() -> Self {
+        LineBuffer::default()
+    }
+
+    pub fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Replace the contents and move the cursor to the end
+    pub fn set_text(&mut self, text: &str) {
+        self.chars = text.chars().collect();
+        self.cursor = self.chars.len();
+    }
+
+    /// Complete the word before the cursor, returning text to show below
+    /// the line: the hint for a single match, or the choices when the word
+    /// cannot be extended unambiguously
+    pub fn complete(&mut self, completer: &dyn Completer) -> Option<String> {
+        let completion = completer.complete(&self.text(), self.cursor);
+        let start = completion.start.min(self.cursor);
+        let word_len = self.cursor - start;
+
+        let replacement = match completion.candidates.as_slice() {
+            [] => return None,
+            [only] => only.clone(),
+            [first, rest @ ..] => rest.iter().fold(first.clone(), |prefix, candidate| {
+                prefix
+                    .chars()
+                    .zip(candidate.chars())
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _)| a)
+                    .collect()
+            }),
+        };
+
+        if replacement.chars().count() > word_len || completion.candidates.len() == 1 {
+            self.chars.splice(start..self.cursor, replacement.chars());
+            self.cursor = start + replacement.chars().count();
+        }
+
+        if completion.candidates.len() == 1 {
+            completion.hint
+        } else if replacement.chars().count() > word_len {
+            None
+        } else {
+            Some(completion.candidates.join("  "))
+        }
+    }
+
+    /// Apply a key press
+    pub fn apply(&mut self, key: Key, history: &History) -> Action {
+        match key {
+            Key::Char(c) => {
+                self.chars.insert(self.cursor, c);
+                self.cursor += 1;
+            }
+            Key::Enter => return Action::Done(ReadResult::Line(self.text())),
+            Key::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.chars.remove(self.cursor);
+            }
+            Key::Delete if self.cursor < self.chars.len() => {
+                self.chars.remove(self.cursor);
+            }
+            Key::Left => self.cursor = self.cursor.saturating_sub(1),
+            Key::Right => self.cursor = (self.cursor + 1).min(self.chars.len()),
+            Key::Home => self.cursor = 0,
+            Key::End => self.cursor = self.chars.len(),
+            Key::KillStart => {
+                self.chars.drain(..self.cursor);
+                self.cursor = 0;
+            }
+            Key::KillEnd => self.chars.truncate(self.cursor),
+            Key::Up => self.history_previous(history),
+            Key::Down => self.history_next(history),
+            Key::Interrupt => return Action::Done(ReadResult::Interrupted),
+            Key::Eof if self.chars.is_empty() => return Action::Done(ReadResult::Eof),
+            Key::Eof => {
+                // Ctrl-D deletes forward on a non-empty line
+                if self.cursor < self.chars.len() {
+                    self.chars.remove(self.cursor);
+                }
+            }
+            // Completion needs a completer; see `complete`
+            Key::Tab | Key::Backspace | Key::Delete | Key::Ignored => {}
+        }
+        Action::Continue
+    }
+
+    fn history_previous(&mut self, history: &History) {
+        let entries = history.entries();
+        let index = match self.history_index {
+            Some(0) => return,
+            Some(index) => index - 1,
+            None if entries.is_empty() => return,
+            None => {
+                self.draft = self.chars.clone();
+                entries.len() - 1
+            }
+        };
+        self.history_index = Some(index);
+        self.set_text(&entries[index]);
+    }
+
+    fn history_next(&mut self, history: &History) {
+        match self.history_index {
+            Some(index) if index + 1 < history.entries().len() => {
+                self.history_index = Some(index + 1);
+                self.set_text(&history.entries()[index + 1]);
+            }
+            Some(_) => {
+                self.history_index = None;
+                self.chars = std::mem::take(&mut self.draft);
+                self.cursor = self.chars.len();
+            }
+            None => {}
+        }
+    }
+}
+
+/// Puts the terminal in raw mode and restores the previous settings on drop
+struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        let saved = stty(&["-g"])?;
+        stty(&["raw", "-echo"])?;
+        Ok(RawMode {
+            saved: saved.trim().to_string(),
+        })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+/// Run `stty` against the controlling terminal
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output()?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(io::Error::new(io::ErrorKind::Other, "stty failed"))
+    }
+}
+
+/// Reads lines from the terminal with editing and history
+pub struct LineEditor {
+    history: History,
+}
+
+impl LineEditor {
+    pub fn new(history: History) -> Self {
+        LineEditor { history }
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    pub fn history_mut(&mut self) -> &mut History {
+        &mut self.history
+    }
+
+    /// Read a line after showing `prompt`, completing words on Tab
+    ///
+    /// Falls back to plain buffered input when stdin or stdout is not a
+    /// terminal or raw mode cannot be enabled.
+    pub fn read_line(&mut self, prompt: &str, completer: &dyn Completer) -> io::Result<ReadResult> {
+        if io::stdin().is_terminal() && io::stdout().is_terminal() {
+            if let Ok(raw) = RawMode::enable() {
+                let result = self.read_line_raw(prompt, completer);
+                drop(raw);
+                return result;
+            }
+        }
+        read_line_plain(prompt)
+    }
+
+    fn read_line_raw(&mut self, prompt: &str, completer: &dyn Completer) -> io::Result<ReadResult> {
+        let mut stdin = io::stdin().lock();
+        let mut stdout = io::stdout();
+        let mut buffer = LineBuffer::new();
+
+        render(&mut stdout, prompt, &buffer)?;
+        loop {
+            let key = read_key(&mut stdin)?;
+            if key == Key::Tab {
+                if let Some(text) = buffer.complete(completer) {
+                    write!(stdout, "\r\n{}\r\n", text.replace('\n', "\r\n"))?;
+                }
+                render(&mut stdout, prompt, &buffer)?;
+                continue;
+            }
+            if let Action::Done(result) = buffer.apply(key, &self.history) {
+                // Raw mode does not translate newlines, so return explicitly
+                write!(stdout, "\r\n")?;
+                stdout.flush()?;
+                return Ok(result);
+            }
+            render(&mut stdout, prompt, &buffer)?;
+        }
+    }
+}
+
+/// Redraw the prompt and line, then place the cursor
+fn render(out: &mut impl Write, prompt: &str, buffer: &LineBuffer) -> io::Result<()> {
+    let text = buffer.text();
+    write!(out, "\r{}{}\x1b[K", prompt, text)?;
+    let back = text.chars().count() - buffer.cursor();
+    if back > 0 {
+        write!(out, "\x1b[{}D", back)?;
+    }
+    out.flush()
+}
+
+fn read_line_plain(prompt: &str) -> io::Result<ReadResult> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        println!();
+        return Ok(ReadResult::Eof);
+    }
+    let line = line.trim_end_matches(['\n', '\r']).to_string();
+    Ok(ReadResult::Line(line))
+}
@@ -0,0 +1,216 @@
+//! Interactive Read-Eval-Print Loop
+//!
+//! `REPL` evaluates input incrementally against one VM; the `editor`
+//! module provides line editing and history for terminal sessions.
+
+mod commands;
+mod complete;
+pub mod editor;
+
+use crate::compiler::{Chunk, Compiler};
+use crate::error::{JingError, JingResult};
+use crate::lexer::{Lexer, Token, TokenType};
+use crate::parser::Parser;
+use crate::value::Value;
+use crate::vm::VM;
+use editor::{History, LineEditor, ReadResult};
+
+/// REPL (Read-Eval-Print Loop) for interactive Jing sessions
+///
+/// Each line is compiled onto the end of the session's chunk, so functions
+/// defined on earlier lines stay callable.
+pub struct REPL {
+    vm: VM,
+    chunk: Chunk,
+    show_banner: bool,
+}
+
+impl REPL {
+    pub fn new() -> Self {
+        REPL {
+            vm: VM::new(),
+            chunk: Chunk::new(),
+            show_banner: true,
+        }
+    }
+
+    /// Choose whether `run` prints the welcome and goodbye messages
+    pub fn set_banner(&mut self, show: bool) {
+        self.show_banner = show;
+    }
+
+    /// The VM holding the session's globals
+    pub fn vm(&self) -> &VM {
+        &self.vm
+    }
+
+    /// Mutable access to the session's VM, e.g. to register builtins
+    pub fn vm_mut(&mut self) -> &mut VM {
+        &mut self.vm
+    }
+
+    /// Evaluate a single line of Jing code, returning the value of its
+    /// final expression (nil for statements)
+    ///
+    /// Non-nil results are also stored in the global `_`.
+    pub fn eval(&mut self, source: &str) -> JingResult<Value> {
+        let result = self.evaluate(source)?;
+        if result != Value::Nil {
+            self.vm.set_global("_", result.clone());
+        }
+        Ok(result)
+    }
+
+    /// Evaluate code in the session without updating `_`
+    fn evaluate(&mut self, source: &str) -> JingResult<Value> {
+        let mut lexer = Lexer::new(source);
+        let mut tokens = lexer.tokenize()?;
+
+        // Let a final expression omit its semicolon, as in `1 + 2`
+        let last = tokens
+            .iter()
+            .rposition(|token| !matches!(token.token_type, TokenType::Newline | TokenType::Eof));
+        if let Some(last) = last {
+            if !matches!(
+                tokens[last].token_type,
+                TokenType::Semicolon | TokenType::RightBrace
+            ) {
+                let line = tokens[last].line;
+                tokens.insert(last + 1, Token::new(TokenType::Semicolon, line));
+            }
+        }
+
+        let mut parser = Parser::new(tokens);
+        let statements = parser.parse()?;
+
+        // Roll a failed compile back so the error leaves the session intact
+        let start = self.chunk.code.len();
+        let mark = self.chunk.mark();
+        let mut compiler = Compiler::with_chunk(std::mem::take(&mut self.chunk));
+        let chunk = match compiler.compile(statements) {
+            Ok(chunk) => chunk,
+            Err(err) => {
+                self.chunk = compiler.into_chunk();
+                self.chunk.rollback(mark);
+                return Err(err);
+            }
+        };
+
+        let result = self.vm.interpret_from(chunk, start);
+        self.chunk = self.vm.take_chunk();
+        result?;
+        self.vm.get_result()
+    }
+
+    /// Start an interactive REPL session
+    ///
+    /// Input continues over several lines (with a `...` prompt) while
+    /// brackets or a string are left open. Lines starting with `:` are
+    /// REPL commands (see `command`). Tab completes keywords and names.
+    /// Lines are saved to `~/.jing_history`. Calling `exit(code)` ends the
+    /// session with `JingError::Exit`.
+    pub fn run(&mut self) -> JingResult<()> {
+        if self.show_banner {
+            println!("Jing REPL v{}", env!("CARGO_PKG_VERSION"));
+            println!("Type ':help' for commands, 'exit' to quit.");
+            println!();
+        }
+
+        let history = History::default_path()
+            .map(History::load)
+            .unwrap_or_default();
+        let mut editor = LineEditor::new(history);
+        let mut source = String::new();
+        let mut exit = None;
+
+        loop {
+            let prompt = if source.is_empty() { "> " } else { "... " };
+            let line = match editor.read_line(prompt, &*self) {
+                Ok(ReadResult::Line(line)) => line,
+                Ok(ReadResult::Interrupted) => {
+                    // Ctrl-C abandons the current input
+                    source.clear();
+                    continue;
+                }
+                Ok(ReadResult::Eof) => break,
+                Err(error) => {
+                    eprintln!("Error reading input: {}", error);
+                    break;
+                }
+            };
+            editor.history_mut().add(&line);
+
+            if source.is_empty() {
+                match line.trim() {
+                    "" => continue,
+                    "exit" | "quit" => break,
+                    command if command.starts_with(':') => {
+                        match self.command(command) {
+                            Ok(output) if output.is_empty() => {}
+                            Ok(output) => println!("{}", output),
+                            Err(err) => eprintln!("Error: {}", err),
+                        }
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+
+            source.push_str(&line);
+            source.push('\n');
+            if needs_continuation(&source) {
+                continue;
+            }
+
+            match self.eval(&source) {
+                Ok(Value::Nil) => {}
+                Ok(value) => println!("{}", value.repr()),
+                Err(err @ JingError::Exit { .. }) => {
+                    exit = Some(err);
+                    break;
+                }
+                Err(err) => {
+                    eprintln!("Error: {}", err);
+                }
+            }
+            source.clear();
+        }
+
+        if let Err(error) = editor.history().save() {
+            eprintln!("Could not save history: {}", error);
+        }
+        if self.show_banner {
+            println!("Goodbye!");
+        }
+        exit.map_or(Ok(()), Err)
+    }
+}
+
+/// Check whether `source` is an incomplete entry that should continue on
+/// the next line: an unclosed `(` or `{` that the parser ran out of input
+/// inside, or an unterminated string
+///
+/// An entry that is already an error before its end, such as `sq(;`, is
+/// submitted so the error is reported.
+pub fn needs_continuation(source: &str) -> bool {
+    let tokens = match Lexer::new(source).tokenize() {
+        Ok(tokens) => tokens,
+        Err(JingError::LexError { message, .. }) => return message == "Unterminated string",
+        Err(_) => return false,
+    };
+
+    let mut depth: isize = 0;
+    for token in &tokens {
+        match token.token_type {
+            TokenType::LeftParen | TokenType::LeftBrace => depth += 1,
+            TokenType::RightParen | TokenType::RightBrace => depth -= 1,
+            _ => {}
+        }
+    }
+    if depth <= 0 {
+        return false;
+    }
+
+    let mut parser = Parser::new(tokens);
+    parser.parse().is_err() && parser.is_at_end()
+}
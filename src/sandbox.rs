@@ -0,0 +1,189 @@
+//! Capability-based sandboxing for builtin functions.
+//!
+//! Every `VM` carries a `Capabilities` value describing what scripts may
+//! do on the host. Builtins that touch the filesystem, standard input or
+//! environment variables consult it before doing any work, so untrusted
+//! scripts can be run with no I/O, read-only access, or access limited to
+//! a set of directories.
+
+use crate::error::{JingError, JingResult};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Filesystem access granted to scripts
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileAccess {
+    /// No filesystem access at all
+    Denied,
+    /// Files may be read and checked for existence, but not written
+    ReadOnly,
+    /// Files may be read and written only inside the listed directories
+    Directories(Vec<PathBuf>),
+    /// Unrestricted access to the host filesystem
+    Full,
+}
+
+/// The set of host capabilities available to scripts running in a VM
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capabilities {
+    pub file_access: FileAccess,
+    pub stdin: bool,
+    pub env: bool,
+}
+
+impl Capabilities {
+    /// Full host access (the default, matching an unsandboxed interpreter)
+    pub fn all() -> Self {
+        Capabilities {
+            file_access: FileAccess::Full,
+            stdin: true,
+            env: true,
+        }
+    }
+
+    /// No filesystem, standard input or environment access
+    pub fn none() -> Self {
+        Capabilities {
+            file_access: FileAccess::Denied,
+            stdin: false,
+            env: false,
+        }
+    }
+
+    /// Read-only filesystem access, no standard input or environment
+    pub fn read_only() -> Self {
+        Capabilities {
+            file_access: FileAccess::ReadOnly,
+            stdin: false,
+            env: false,
+        }
+    }
+
+    /// Filesystem access limited to the given directories, no standard input
+    /// or environment
+    pub fn directories<P: Into<PathBuf>>(dirs: impl IntoIterator<Item = P>) -> Self {
+        Capabilities {
+            file_access: FileAccess::Directories(dirs.into_iter().map(Into::into).collect()),
+            stdin: false,
+            env: false,
+        }
+    }
+
+    /// Allow or deny reading from standard input
+    pub fn with_stdin(mut self, allowed: bool) -> Self {
+        self.stdin = allowed;
+        self
+    }
+
+    /// Allow or deny reading and setting environment variables
+    pub fn with_env(mut self, allowed: bool) -> Self {
+        self.env = allowed;
+        self
+    }
+
+    /// Check that `path` may be read, returning the path to open
+    pub fn check_read(&self, path: &str) -> JingResult<PathBuf> {
+        match &self.file_access {
+            FileAccess::Denied => Err(JingError::permission_denied(format!(
+                "filesystem access is disabled (reading '{}')",
+                path
+            ))),
+            FileAccess::ReadOnly | FileAccess::Full => Ok(PathBuf::from(path)),
+            FileAccess::Directories(dirs) => check_inside(dirs, path),
+        }
+    }
+
+    /// Check that `path` may be written, returning the path to open
+    pub fn check_write(&self, path: &str) -> JingResult<PathBuf> {
+        match &self.file_access {
+            FileAccess::Denied => Err(JingError::permission_denied(format!(
+                "filesystem access is disabled (writing '{}')",
+                path
+            ))),
+            FileAccess::ReadOnly => Err(JingError::permission_denied(format!(
+                "filesystem is read-only (writing '{}')",
+                path
+            ))),
+            FileAccess::Full => Ok(PathBuf::from(path)),
+            FileAccess::Directories(dirs) => check_inside(dirs, path),
+        }
+    }
+
+    /// Check that standard input may be read
+    pub fn check_stdin(&self) -> JingResult<()> {
+        if self.stdin {
+            Ok(())
+        } else {
+            Err(JingError::permission_denied("standard input is disabled"))
+        }
+    }
+
+    /// Check that environment variables may be read or set
+    pub fn check_env(&self) -> JingResult<()> {
+        if self.env {
+            Ok(())
+        } else {
+            Err(JingError::permission_denied(
+                "environment access is disabled",
+            ))
+        }
+    }
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Capabilities::all()
+    }
+}
+
+/// Resolve `path` and make sure it lies inside one of `dirs`
+fn check_inside(dirs: &[PathBuf], path: &str) -> JingResult<PathBuf> {
+    let resolved = canonicalize_lenient(Path::new(path))
+        .ok_or_else(|| JingError::permission_denied(format!("cannot resolve path '{}'", path)))?;
+
+    let allowed = dirs
+        .iter()
+        .filter_map(|dir| fs::canonicalize(dir).ok())
+        .any(|dir| resolved.starts_with(dir));
+
+    if allowed {
+        Ok(resolved)
+    } else {
+        Err(JingError::permission_denied(format!(
+            "'{}' is outside the allowed directories",
+            path
+        )))
+    }
+}
+
+/// Most symlinks followed while resolving a path, as with `SYMLOOP_MAX`
+const MAX_SYMLINKS: usize = 40;
+
+/// Canonicalize a path that may not exist yet by resolving its parent.
+///
+/// When the last component is a dangling symlink, opening the path for
+/// writing would create the link's target, so the target is resolved in
+/// its place. `None` is returned for symlink loops.
+fn canonicalize_lenient(path: &Path) -> Option<PathBuf> {
+    let mut path = path.to_path_buf();
+    for _ in 0..MAX_SYMLINKS {
+        if let Ok(resolved) = fs::canonicalize(&path) {
+            return Some(resolved);
+        }
+
+        let file_name = path.file_name()?.to_owned();
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let resolved = fs::canonicalize(parent).ok()?.join(file_name);
+
+        match fs::read_link(&resolved) {
+            // Relative targets are relative to the link's directory; an
+            // absolute target replaces the whole path
+            Ok(target) => path = resolved.parent()?.join(target),
+            Err(_) => return Some(resolved),
+        }
+    }
+    None
+}
@@ -0,0 +1,325 @@
+//! Lossless concrete syntax tree (CST) for tooling
+//!
+//! The AST built by `Parser::parse` keeps only what the compiler needs. The
+//! CST instead keeps every byte of the source: each token carries its text
+//! and byte span, and whitespace, newlines and comments are kept as trivia
+//! tokens between them. Joining the text of all tokens in a tree gives back
+//! the source exactly, so formatters, refactoring tools and the language
+//! server can edit code without losing anything.
+//!
+//! The tree is built from the same parser as the AST: in CST mode the
+//! parser records where each node starts and finishes, and `parse` lays
+//! those events over the lossless token stream. Trivia between two nodes
+//! belongs to their parent.
+
+use crate::error::JingError;
+use crate::lexer::{Lexer, Span, TokenType};
+use crate::parser::Parser;
+use std::fmt;
+
+/// What a token in the lossless stream is
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    /// A token the parser sees
+    Token(TokenType),
+    /// Spaces, tabs and carriage returns
+    Whitespace,
+    Newline,
+    /// A `//` comment up to the end of its line
+    Comment,
+    /// The `#!` line at the start of a script
+    Shebang,
+    /// Text the lexer could not read, such as `$` or an unterminated string
+    Error,
+}
+
+impl TokenKind {
+    /// Whether the parser skips the token: whitespace, a newline, a comment
+    /// or unreadable text
+    pub fn is_trivia(&self) -> bool {
+        !matches!(self, TokenKind::Token(_))
+    }
+}
+
+/// A token together with its exact source text
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxToken {
+    pub kind: TokenKind,
+    pub text: String,
+    pub span: Span,
+}
+
+impl SyntaxToken {
+    /// The token of `kind` covering `span` of `source`
+    pub fn new(kind: TokenKind, source: &str, span: Span) -> Self {
+        SyntaxToken {
+            kind,
+            text: source[span.start..span.end].to_string(),
+            span,
+        }
+    }
+}
+
+/// Kinds of CST nodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    /// The whole program
+    Root,
+    ImportStmt,
+    ExportStmt,
+    LetStmt,
+    FnStmt,
+    /// `(a, b = 1, ...rest)` in a function declaration
+    ParamList,
+    Param,
+    Block,
+    IfStmt,
+    WhileStmt,
+    ReturnStmt,
+    /// An expression used as a statement, including `print(...)`
+    ExprStmt,
+    AssignExpr,
+    LogicalExpr,
+    BinaryExpr,
+    UnaryExpr,
+    CallExpr,
+    /// `(1, port: 80)` in a call
+    ArgList,
+    NamedArg,
+    GetExpr,
+    ParenExpr,
+    Literal,
+    /// A variable read
+    NameRef,
+    /// Source the parser could not make sense of
+    Error,
+}
+
+/// A child of a CST node
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxElement {
+    pub fn span(&self) -> Span {
+        match self {
+            SyntaxElement::Node(node) => node.span,
+            SyntaxElement::Token(token) => token.span,
+        }
+    }
+}
+
+/// A node of the CST, covering the bytes of all its children
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxNode {
+    pub kind: NodeKind,
+    pub span: Span,
+    pub children: Vec<SyntaxElement>,
+}
+
+impl SyntaxNode {
+    fn new(kind: NodeKind, start: usize) -> Self {
+        SyntaxNode {
+            kind,
+            span: Span::new(start, start),
+            children: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, element: SyntaxElement) {
+        self.span.end = element.span().end;
+        self.children.push(element);
+    }
+
+    /// The source text covered by the node, trivia included
+    pub fn text(&self) -> String {
+        self.tokens().map(|token| token.text.as_str()).collect()
+    }
+
+    /// Direct child nodes
+    pub fn child_nodes(&self) -> impl Iterator<Item = &SyntaxNode> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// The node and every node under it, in source order
+    pub fn descendants(&self) -> Vec<&SyntaxNode> {
+        let mut nodes = vec![self];
+        for child in self.child_nodes() {
+            nodes.extend(child.descendants());
+        }
+        nodes
+    }
+
+    /// All tokens under the node, trivia included, in source order
+    pub fn tokens(&self) -> Box<dyn Iterator<Item = &SyntaxToken> + '_> {
+        Box::new(self.children.iter().flat_map(|child| match child {
+            SyntaxElement::Node(node) => node.tokens(),
+            SyntaxElement::Token(token) => Box::new(std::iter::once(token)),
+        }))
+    }
+
+    /// The innermost node whose span contains byte `offset`
+    pub fn node_at(&self, offset: usize) -> Option<&SyntaxNode> {
+        if !self.span.contains(offset) {
+            return None;
+        }
+        Some(
+            self.child_nodes()
+                .find_map(|node| node.node_at(offset))
+                .unwrap_or(self),
+        )
+    }
+
+    /// The non-trivia token that covers byte `offset`
+    ///
+    /// An offset just past the end of a token still finds it when no token
+    /// starts there, which is where an editor cursor sits after typing a
+    /// name.
+    pub fn token_at(&self, offset: usize) -> Option<&SyntaxToken> {
+        let mut touching = None;
+        for token in self.tokens().filter(|token| !token.kind.is_trivia()) {
+            if token.span.start <= offset && offset < token.span.end {
+                return Some(token);
+            }
+            if token.span.end == offset {
+                touching = Some(token);
+            }
+        }
+        touching
+    }
+
+    fn write_tree(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        writeln!(
+            f,
+            "{:indent$}{:?}@{}..{}",
+            "",
+            self.kind,
+            self.span.start,
+            self.span.end,
+            indent = depth * 2
+        )?;
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => node.write_tree(f, depth + 1)?,
+                SyntaxElement::Token(token) => writeln!(
+                    f,
+                    "{:indent$}{:?}@{}..{} {:?}",
+                    "",
+                    token.kind,
+                    token.span.start,
+                    token.span.end,
+                    token.text,
+                    indent = (depth + 1) * 2
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Prints the tree one element per line, indented by depth
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_tree(f, 0)
+    }
+}
+
+/// A parsed CST and the parse error, if any
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxTree {
+    pub root: SyntaxNode,
+    /// The first lexical error, or else the first parse error
+    ///
+    /// Each statement that failed to parse is kept under an `Error` node
+    /// reaching to the next `;` or `}`; the statements after it, in the
+    /// same block or further on, are parsed as usual.
+    pub error: Option<JingError>,
+}
+
+/// What the parser did, recorded in CST mode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Event {
+    Start(NodeKind),
+    /// The parser consumed the next token
+    Token,
+    Finish,
+}
+
+/// Parse `source` into a lossless CST
+///
+/// Errors never stop the tree from covering all of the source: text the
+/// lexer cannot read becomes an error token, and see `SyntaxTree::error`
+/// for parse errors.
+pub fn parse(source: &str) -> SyntaxTree {
+    let mut lexer = Lexer::new(source);
+    let (tokens, lex_error) = lexer.tokenize_tolerant();
+    let lossless = lexer.lossless(&tokens);
+
+    let mut parser = Parser::new(tokens);
+    let (result, events) = parser.parse_events();
+
+    let mut builder = Builder {
+        tokens: lossless.into_iter().peekable(),
+        stack: vec![SyntaxNode::new(NodeKind::Root, 0)],
+    };
+    for event in events {
+        match event {
+            Event::Start(kind) => builder.start(kind),
+            Event::Token => builder.token(),
+            Event::Finish => builder.finish(),
+        }
+    }
+
+    builder.trivia();
+
+    SyntaxTree {
+        root: builder.stack.pop().expect("root node"),
+        error: lex_error.or(result.err()),
+    }
+}
+
+/// Lays parser events over the lossless token stream
+struct Builder {
+    tokens: std::iter::Peekable<std::vec::IntoIter<SyntaxToken>>,
+    /// Open nodes, the root first
+    stack: Vec<SyntaxNode>,
+}
+
+impl Builder {
+    fn current(&mut self) -> &mut SyntaxNode {
+        self.stack.last_mut().expect("root node")
+    }
+
+    /// Move pending trivia into the innermost open node
+    fn trivia(&mut self) {
+        while let Some(token) = self.tokens.next_if(|token| token.kind.is_trivia()) {
+            self.current().push(SyntaxElement::Token(token));
+        }
+    }
+
+    fn start(&mut self, kind: NodeKind) {
+        self.trivia();
+        let start = self
+            .tokens
+            .peek()
+            .map_or(self.stack[0].span.end, |token| token.span.start);
+        self.stack.push(SyntaxNode::new(kind, start));
+    }
+
+    fn token(&mut self) {
+        self.trivia();
+        if let Some(token) = self.tokens.next() {
+            self.current().push(SyntaxElement::Token(token));
+        }
+    }
+
+    fn finish(&mut self) {
+        let node = self.stack.pop().expect("open node");
+        self.current().push(SyntaxElement::Node(node));
+    }
+}
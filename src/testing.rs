@@ -0,0 +1,172 @@
+//! Test runner behind `jing test`
+//!
+//! Test files end in `_test.jing` and define functions whose names start
+//! with `test_`. Each test runs in a fresh `VM`: the file's top-level code
+//! runs first and then the test function is called, so tests cannot see
+//! each other's changes to globals.
+
+use crate::compiler::{Chunk, Compiler};
+use crate::error::{JingError, JingResult};
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::vm::VM;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// File name ending that marks a test file
+pub const TEST_FILE_SUFFIX: &str = "_test.jing";
+/// Name prefix that marks a test function
+pub const TEST_PREFIX: &str = "test_";
+
+/// The outcome of running one test function
+#[derive(Debug, Clone)]
+pub struct TestResult {
+    pub name: String,
+    /// `Err(JingError::AssertionFailed)` for a failed check, or any other
+    /// error the test ran into
+    pub outcome: JingResult<()>,
+    pub duration: Duration,
+}
+
+impl TestResult {
+    pub fn passed(&self) -> bool {
+        self.outcome.is_ok()
+    }
+}
+
+/// The results of one test file
+#[derive(Debug, Clone)]
+pub struct FileReport {
+    pub path: PathBuf,
+    pub results: Vec<TestResult>,
+    /// Tests skipped because their names did not match the filter
+    pub filtered: usize,
+    /// Set when the file could not be read or compiled
+    pub error: Option<JingError>,
+}
+
+/// Find test files under `paths` (the current directory when empty)
+///
+/// Files named explicitly are always included; directories are searched
+/// recursively for `*_test.jing`, skipping hidden directories and `target`.
+pub fn discover(paths: &[String]) -> JingResult<Vec<PathBuf>> {
+    let roots = if paths.is_empty() {
+        vec![PathBuf::from(".")]
+    } else {
+        paths.iter().map(PathBuf::from).collect()
+    };
+
+    let mut files = Vec::new();
+    for root in roots {
+        if root.is_file() {
+            files.push(root);
+        } else if root.is_dir() {
+            collect_test_files(&root, &mut files)?;
+        } else {
+            return Err(JingError::io_error(format!(
+                "Could not find '{}'",
+                root.display()
+            )));
+        }
+    }
+
+    // Show `./a_test.jing` as `a_test.jing`
+    Ok(files
+        .into_iter()
+        .map(|path| match path.strip_prefix(".") {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => path,
+        })
+        .collect())
+}
+
+fn collect_test_files(dir: &Path, files: &mut Vec<PathBuf>) -> JingResult<()> {
+    let entries = fs::read_dir(dir).map_err(|err| {
+        JingError::io_error(format!("Could not read '{}': {}", dir.display(), err))
+    })?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if path.is_dir() {
+            if !name.starts_with('.') && name != "target" {
+                collect_test_files(&path, files)?;
+            }
+        } else if name.ends_with(TEST_FILE_SUFFIX) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Names of the test functions in `chunk`, in the order they are defined
+pub fn test_names(chunk: &Chunk) -> Vec<String> {
+    let mut tests: Vec<_> = chunk
+        .functions
+        .values()
+        .filter(|info| info.name.starts_with(TEST_PREFIX))
+        .collect();
+    tests.sort_by_key(|info| info.start_address);
+    tests.into_iter().map(|info| info.name.clone()).collect()
+}
+
+/// Run the tests in one file whose names contain `filter`
+pub fn run_file(path: &Path, filter: Option<&str>) -> FileReport {
+    let mut report = FileReport {
+        path: path.to_path_buf(),
+        results: Vec::new(),
+        filtered: 0,
+        error: None,
+    };
+
+    let chunk = match compile_file(path) {
+        Ok(chunk) => chunk,
+        Err(err) => {
+            report.error = Some(err);
+            return report;
+        }
+    };
+
+    for name in test_names(&chunk) {
+        if filter.is_some_and(|filter| !name.contains(filter)) {
+            report.filtered += 1;
+            continue;
+        }
+        report.results.push(run_test(&chunk, path, &name));
+    }
+    report
+}
+
+fn compile_file(path: &Path) -> JingResult<Chunk> {
+    let source = fs::read_to_string(path).map_err(|err| {
+        JingError::io_error(format!("Could not read file '{}': {}", path.display(), err))
+    })?;
+    let tokens = Lexer::new(&source).tokenize()?;
+    let statements = Parser::new(tokens).parse()?;
+    Compiler::new().compile(statements)
+}
+
+/// Run the file's top-level code and then one test function in a fresh VM
+fn run_test(chunk: &Chunk, path: &Path, name: &str) -> TestResult {
+    let start = Instant::now();
+
+    let mut vm = VM::new();
+    vm.set_script_path(path);
+    let outcome = vm
+        .interpret(chunk.clone())
+        .and_then(|_| vm.call(name, &[]))
+        .map(|_| ());
+
+    TestResult {
+        name: name.to_string(),
+        outcome,
+        duration: start.elapsed(),
+    }
+}
@@ -1,6 +1,6 @@
 use crate::error::{JingError, JingResult};
-use crate::features::BuiltinFunction;
-use std::collections::HashMap;
+use crate::features::{Arity, BuiltinFunction};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::sync::Arc;
 
@@ -11,10 +11,14 @@ pub enum Value {
     Bool(bool),
     Number(f64),
     String(String),
+    List(Vec<Value>),
+    Map(BTreeMap<String, Value>),
     Function {
         name: String,
-        arity: usize,
+        arity: Arity,
         chunk_start: usize,
+        /// Index of the VM module whose chunk holds the function's code
+        module: usize,
     },
     BuiltinFunction {
         name: String,
@@ -35,6 +39,17 @@ impl fmt::Display for Value {
                 }
             }
             Value::String(s) => write!(f, "{}", s),
+            Value::List(items) => {
+                let items: Vec<String> = items.iter().map(Value::repr).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+            Value::Map(entries) => {
+                let entries: Vec<String> = entries
+                    .iter()
+                    .map(|(key, value)| format!("{:?}: {}", key, value.repr()))
+                    .collect();
+                write!(f, "{{{}}}", entries.join(", "))
+            }
             Value::Function { name, arity, .. } => {
                 write!(f, "<fn {}({} args)>", name, arity)
             }
@@ -67,11 +82,37 @@ impl Value {
             Value::Bool(_) => "bool",
             Value::Number(_) => "number",
             Value::String(_) => "string",
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
             Value::Function { .. } => "function",
             Value::BuiltinFunction { .. } => "builtin_function",
         }
     }
 
+    /// Number of heap bytes owned by this value, used for memory accounting
+    pub fn heap_size(&self) -> usize {
+        match self {
+            Value::String(s) => s.len(),
+            Value::List(items) => {
+                items.len() * std::mem::size_of::<Value>()
+                    + items.iter().map(Value::heap_size).sum::<usize>()
+            }
+            Value::Map(entries) => entries
+                .iter()
+                .map(|(key, value)| key.len() + std::mem::size_of::<Value>() + value.heap_size())
+                .sum(),
+            _ => 0,
+        }
+    }
+
+    /// Source-like representation: strings are quoted, other values display as usual
+    pub fn repr(&self) -> String {
+        match self {
+            Value::String(s) => format!("{:?}", s),
+            other => other.to_string(),
+        }
+    }
+
     /// Convert value to string representation
     /// Convert value to string representation for concatenation
     pub fn as_string(&self) -> String {
@@ -193,6 +234,14 @@ impl Value {
             (Value::Bool(a), Value::Bool(b)) => a == b,
             (Value::Number(a), Value::Number(b)) => (a - b).abs() < f64::EPSILON,
             (Value::String(a), Value::String(b)) => a == b,
+            (Value::List(a), Value::List(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.equals(y))
+            }
+            (Value::Map(a), Value::Map(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(key, x)| b.get(key).map_or(false, |y| x.equals(y)))
+            }
             _ => false,
         }
     }
@@ -265,6 +314,18 @@ impl Environment {
         )))
     }
 
+    /// Names defined in any scope, sorted alphabetically
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .scopes
+            .iter()
+            .flat_map(|scope| scope.keys().cloned())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
     pub fn set(&mut self, name: &str, value: Value) -> JingResult<()> {
         for scope in self.scopes.iter_mut().rev() {
             if scope.contains_key(name) {
@@ -287,18 +348,22 @@ impl PartialEq for Value {
             (Value::Bool(a), Value::Bool(b)) => a == b,
             (Value::Number(a), Value::Number(b)) => a == b,
             (Value::String(a), Value::String(b)) => a == b,
+            (Value::List(a), Value::List(b)) => a == b,
+            (Value::Map(a), Value::Map(b)) => a == b,
             (
                 Value::Function {
                     name: n1,
                     arity: a1,
                     chunk_start: c1,
+                    module: m1,
                 },
                 Value::Function {
                     name: n2,
                     arity: a2,
                     chunk_start: c2,
+                    module: m2,
                 },
-            ) => n1 == n2 && a1 == a2 && c1 == c2,
+            ) => n1 == n2 && a1 == a2 && c1 == c2 && m1 == m2,
             (Value::BuiltinFunction { name: n1, .. }, Value::BuiltinFunction { name: n2, .. }) => {
                 // Compare builtin functions by name only
                 n1 == n2
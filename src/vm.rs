@@ -1,58 +1,354 @@
-use crate::compiler::{Chunk, OpCode};
+use crate::builtins::native::IntoNativeFunction;
+use crate::compiler::{Chunk, Compiler, FunctionInfo, OpCode};
+use crate::debugger::DebugHook;
 use crate::error::{JingError, JingResult};
+use crate::features::CallContext;
+use crate::lexer::Lexer;
+use crate::module::{self, Module};
+use crate::parser::Parser;
+use crate::registry::Registry;
+use crate::sandbox::Capabilities;
 use crate::value::{Environment, Value};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Re-exported so existing `jing::vm::REPL` imports keep working
+pub use crate::repl::REPL;
 
 /// Call frame for function calls
 #[derive(Debug, Clone)]
 struct CallFrame {
-    #[allow(dead_code)]
     function_name: String,
     return_address: usize,
+    return_module: usize, // Module to switch back to on return
     stack_base: usize,
     locals: Environment, // Local variable environment for this function call
 }
 
+/// How many builtin callbacks into scripts may be nested, such as `map`
+/// calling a function that calls `map` again
+///
+/// Each one runs the VM again on the Rust stack, so without a limit deep
+/// nesting would overflow it and abort the process. The limit leaves room
+/// on a 2 MiB thread stack even in debug builds.
+pub const MAX_NATIVE_DEPTH: usize = 32;
+
 /// Virtual Machine for executing Jing bytecode
 pub struct VM {
-    chunk: Chunk,
-    ip: usize,         // Instruction pointer
-    stack: Vec<Value>, // Value stack
-    globals: Environment,
+    modules: Vec<Module>, // The main script (index 0) and imported modules
+    module: usize,        // Module whose code is running
+    ip: usize,            // Instruction pointer
+    stack: Vec<Value>,    // Value stack
     call_stack: Vec<CallFrame>,
+    memory_limit: Option<usize>,  // Maximum bytes scripts may allocate
+    memory_used: usize,           // Bytes allocated by the current run
+    capabilities: Capabilities,   // Host access granted to builtins
+    registry: Registry,           // Builtin functions visible to scripts
+    args: Vec<String>,            // Command-line arguments passed to the script
+    env: HashMap<String, String>, // Variables set by `set_env`, shadowing the process's
+    debug_hook: Option<Box<dyn DebugHook>>, // Called before each instruction in debug mode
+    hidden_depth: usize,          // Call depth of importers whose frames are set aside
+    native_depth: usize,          // Nested `call_value` runs on the Rust stack
+}
+
+/// A call frame as seen by a debugger, from `VM::frames`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    /// Name of the function, or `None` for a module's top-level code
+    pub function: Option<String>,
+    /// File of the imported module the code belongs to; `None` for the
+    /// main script
+    pub module: Option<PathBuf>,
+    /// Line of the statement being run, when the chunk has a line table
+    pub line: Option<usize>,
+    /// Parameters bound in the frame, sorted by name
+    pub locals: Vec<(String, Value)>,
 }
 
 impl VM {
     pub fn new() -> Self {
+        VM::with_registry(Registry::with_defaults())
+    }
+
+    /// Create a VM exposing only the builtins in `registry`
+    pub fn with_registry(registry: Registry) -> Self {
         VM {
-            chunk: Chunk::new(),
+            modules: vec![Module::main(None)],
+            module: 0,
             ip: 0,
             stack: Vec::new(),
-            globals: Environment::new(),
             call_stack: Vec::new(),
+            memory_limit: None,
+            memory_used: 0,
+            capabilities: Capabilities::all(),
+            registry,
+            args: Vec::new(),
+            env: HashMap::new(),
+            debug_hook: None,
+            hidden_depth: 0,
+            native_depth: 0,
         }
     }
 
+    /// Get the builtin functions available to this VM
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Get mutable access to this VM's builtins, to add or remove functions
+    pub fn registry_mut(&mut self) -> &mut Registry {
+        &mut self.registry
+    }
+
+    /// Register a Rust closure as a builtin function for this VM
+    ///
+    /// ```rust
+    /// let mut vm = jing::VM::new();
+    /// vm.register_fn("add", |a: f64, b: f64| a + b);
+    /// ```
+    pub fn register_fn<Args, F: IntoNativeFunction<Args>>(&mut self, name: &str, function: F) {
+        self.registry.register_fn(name, function);
+    }
+
+    /// Restrict the host access available to builtins run by this VM
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.capabilities = capabilities;
+    }
+
+    /// Get the sandbox capabilities of this VM
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    /// Set the command-line arguments returned by the `args()` builtin
+    pub fn set_args(&mut self, args: Vec<String>) {
+        self.args = args;
+    }
+
+    /// Get the command-line arguments passed to the script
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Get an environment variable as scripts see it
+    ///
+    /// Variables set with `set_env_var` shadow the process environment.
+    pub fn env_var(&self, name: &str) -> Option<String> {
+        match self.env.get(name) {
+            Some(value) => Some(value.clone()),
+            None => env::var(name).ok(),
+        }
+    }
+
+    /// Set an environment variable for scripts run by this VM
+    ///
+    /// The process environment is left unchanged, so VMs in the same
+    /// process cannot see each other's variables.
+    pub fn set_env_var(&mut self, name: &str, value: &str) {
+        self.env.insert(name.to_string(), value.to_string());
+    }
+
+    /// Set the file the main script was loaded from
+    ///
+    /// Relative imports in the main script are resolved from its directory;
+    /// without a path they are resolved from the current directory.
+    pub fn set_script_path(&mut self, path: impl Into<PathBuf>) {
+        let path = path.into();
+        self.modules[0].path = Some(fs::canonicalize(&path).unwrap_or(path));
+    }
+
+    /// Limit the number of bytes a script may allocate for strings.
+    ///
+    /// The limit applies to each top-level run: `interpret`,
+    /// `interpret_from` and `call` start counting from zero, so a VM can
+    /// serve any number of host calls. Within a run, allocations are not
+    /// released when values are dropped, so the limit bounds the total a
+    /// run can request. Exceeding it fails with
+    /// `JingError::ResourceExhausted`.
+    pub fn set_memory_limit(&mut self, limit: Option<usize>) {
+        self.memory_limit = limit;
+    }
+
+    /// Get the configured memory limit, if any
+    pub fn memory_limit(&self) -> Option<usize> {
+        self.memory_limit
+    }
+
+    /// Get the number of bytes allocated since the current or last
+    /// top-level run started; see `set_memory_limit`
+    pub fn memory_used(&self) -> usize {
+        self.memory_used
+    }
+
+    /// Run in debug mode, calling `hook` before each instruction
+    ///
+    /// The hook can inspect the VM through `line_start`, `call_depth`,
+    /// `frames`, `stack` and `current_globals`, and stops the script by
+    /// returning an error. `None` turns debug mode off.
+    pub fn set_debug_hook(&mut self, hook: Option<Box<dyn DebugHook>>) {
+        self.debug_hook = hook;
+    }
+
     /// Load and execute a chunk of bytecode
     pub fn interpret(&mut self, chunk: Chunk) -> JingResult<()> {
-        self.chunk = chunk;
+        self.memory_used = 0;
+        self.modules[0].chunk = chunk;
+        self.module = 0;
         self.ip = 0;
         self.run()
     }
 
+    /// Execute a chunk starting at `start`, keeping globals from earlier runs
+    ///
+    /// Used with `Compiler::with_chunk` to run code appended to a chunk
+    /// that was interpreted before, such as successive REPL lines.
+    pub fn interpret_from(&mut self, chunk: Chunk, start: usize) -> JingResult<()> {
+        self.memory_used = 0;
+        self.modules[0].chunk = chunk;
+        self.module = 0;
+        self.ip = start;
+        self.stack.clear();
+        self.call_stack.clear();
+        self.run()
+    }
+
+    /// Take back the chunk of the last script run, leaving an empty one
+    ///
+    /// Lets a caller that runs a growing chunk with `interpret_from` keep
+    /// extending it without copying it for every run.
+    pub fn take_chunk(&mut self) -> Chunk {
+        std::mem::take(&mut self.modules[0].chunk)
+    }
+
+    /// Call a function defined by a previously loaded script, by name
+    ///
+    /// The function runs in a fresh call frame on top of the current VM
+    /// state, so globals set by earlier scripts stay visible and any
+    /// changes the function makes to them are kept.
+    pub fn call(&mut self, name: &str, args: &[Value]) -> JingResult<Value> {
+        let function = self
+            .resolve(name)
+            .ok_or_else(|| JingError::runtime_error(format!("Undefined function '{}'", name)))?;
+        self.memory_used = 0;
+        self.call_value(function, args.to_vec())
+    }
+
+    /// Call a Jing function or builtin value with the given arguments
+    ///
+    /// Builtins use this for callbacks, so unlike `call` it adds to the
+    /// memory counted for the current run.
+    pub fn call_value(&mut self, function: Value, args: Vec<Value>) -> JingResult<Value> {
+        if !matches!(
+            function,
+            Value::Function { .. } | Value::BuiltinFunction { .. }
+        ) {
+            return Err(JingError::runtime_error(format!(
+                "Can only call functions, got {}",
+                function.type_name()
+            )));
+        }
+
+        if self.native_depth >= MAX_NATIVE_DEPTH {
+            return Err(JingError::resource_exhausted(format!(
+                "builtins called back into scripts more than {} levels deep",
+                MAX_NATIVE_DEPTH
+            )));
+        }
+
+        let saved_ip = self.ip;
+        let saved_module = self.module;
+        let stack_base = self.stack.len();
+        let depth = self.call_stack.len();
+        let arity = args.len();
+
+        for arg in args {
+            self.push(arg);
+        }
+        self.push(function);
+
+        // Builtins complete immediately; Jing functions run until their frame returns
+        let result = self
+            .call_function(arity)
+            .and_then(|_| {
+                if self.call_stack.len() > depth {
+                    self.native_depth += 1;
+                    let result = self.run_until(Some(depth));
+                    self.native_depth -= 1;
+                    result
+                } else {
+                    Ok(())
+                }
+            })
+            .and_then(|_| self.pop());
+
+        // Restore the caller's state, even if the call failed part-way
+        self.ip = saved_ip;
+        self.module = saved_module;
+        self.call_stack.truncate(depth);
+        self.stack.truncate(stack_base);
+
+        result
+    }
+
+    /// Look up a name as a local, global, script function or builtin
+    fn resolve(&self, name: &str) -> Option<Value> {
+        // First try to load from current function's local scope
+        if let Some(current_frame) = self.call_stack.last() {
+            if let Ok(value) = current_frame.locals.get(name) {
+                return Some(value);
+            }
+        }
+
+        // Then try to load from the running module's globals (variables)
+        let module = &self.modules[self.module];
+        if let Ok(value) = module.globals.get(name) {
+            Some(value)
+        } else if let Some(func_info) = module.chunk.functions.get(name) {
+            // If not found in globals, try to load as a function
+            Some(Value::Function {
+                name: func_info.name.clone(),
+                arity: func_info.arity,
+                chunk_start: func_info.start_address,
+                module: self.module,
+            })
+        } else {
+            // Check for builtin functions
+            self.registry
+                .get(name)
+                .map(|builtin| Value::BuiltinFunction {
+                    name: name.to_string(),
+                    function: builtin,
+                })
+        }
+    }
+
     /// Main execution loop
     fn run(&mut self) -> JingResult<()> {
+        self.run_until(None)
+    }
+
+    /// Execute instructions until the program halts or, when `stop_depth`
+    /// is given, until a return brings the call stack back to that depth
+    fn run_until(&mut self, stop_depth: Option<usize>) -> JingResult<()> {
         loop {
-            if self.ip >= self.chunk.code.len() {
+            if self.ip >= self.modules[self.module].chunk.code.len() {
                 break;
             }
+            if self.debug_hook.is_some() {
+                self.debug_check()?;
+            }
+
+            let chunk = &self.modules[self.module].chunk;
 
-            let instruction = self.chunk.code[self.ip].clone();
+            let instruction = chunk.code[self.ip].clone();
             self.ip += 1;
 
             match instruction {
                 OpCode::Constant(index) => {
-                    if index < self.chunk.constants.len() {
-                        let value = self.chunk.constants[index].clone();
+                    if let Some(value) = self.modules[self.module].chunk.constants.get(index) {
+                        let value = value.clone();
                         self.push(value);
                     } else {
                         return Err(JingError::runtime_error("Invalid constant index"));
@@ -60,52 +356,70 @@ impl VM {
                 }
 
                 OpCode::Load(name) => {
-                    // First try to load from current function's local scope
-                    if let Some(current_frame) = self.call_stack.last() {
-                        if let Ok(value) = current_frame.locals.get(&name) {
-                            self.push(value);
-                            continue;
-                        }
-                    }
-
-                    // Then try to load from globals (variables)
-                    if let Ok(value) = self.globals.get(&name) {
-                        self.push(value);
-                    } else if let Some(func_info) = self.chunk.functions.get(&name) {
-                        // If not found in globals, try to load as a function
-                        let function_value = Value::Function {
-                            name: func_info.name.clone(),
-                            arity: func_info.arity,
-                            chunk_start: func_info.start_address,
-                        };
-                        self.push(function_value);
-                    } else if let Some(builtin) = crate::registry::get_builtin(&name) {
-                        // Check for builtin functions
-                        let builtin_value = Value::BuiltinFunction {
-                            name: name.clone(),
-                            function: builtin,
-                        };
-                        self.push(builtin_value);
-                    } else {
-                        return Err(JingError::runtime_error(format!(
+                    let value = self.resolve(&name).ok_or_else(|| {
+                        JingError::runtime_error(format!(
                             "Undefined variable or function '{}'",
                             name
-                        )));
-                    }
+                        ))
+                    })?;
+                    self.push(value);
                 }
 
                 OpCode::Store(name) => {
                     let value = self.pop()?;
-                    self.globals.define(name, value);
+                    self.modules[self.module].globals.define(name, value);
                 }
 
                 OpCode::Pop => {
                     self.pop()?;
                 }
 
+                OpCode::JumpIfBound(name, address) => {
+                    let bound = self
+                        .call_stack
+                        .last()
+                        .is_some_and(|frame| frame.locals.get(&name).is_ok());
+                    if bound {
+                        self.ip = address;
+                    }
+                }
+
+                OpCode::BindLocal(name) => {
+                    let value = self.pop()?;
+                    match self.call_stack.last_mut() {
+                        Some(frame) => frame.locals.define(name, value),
+                        None => self.modules[self.module].globals.define(name, value),
+                    }
+                }
+
+                OpCode::Import(spec) => {
+                    let namespace = self.import(&spec)?;
+                    self.push(namespace);
+                }
+
+                OpCode::GetField(name) => {
+                    let value = match self.pop()? {
+                        Value::Map(mut entries) => entries.remove(&name).ok_or_else(|| {
+                            JingError::runtime_error(format!("Undefined field '{}'", name))
+                        })?,
+                        other => {
+                            return Err(JingError::type_error(format!(
+                                "Cannot read field '{}' of {}",
+                                name,
+                                other.type_name()
+                            )))
+                        }
+                    };
+                    self.push(value);
+                }
+
                 OpCode::Add => {
                     let b = self.pop()?;
                     let a = self.pop()?;
+                    // Charge string concatenation before allocating the result
+                    if matches!(a, Value::String(_)) || matches!(b, Value::String(_)) {
+                        self.track_allocation(a.heap_size() + b.heap_size())?;
+                    }
                     let result = a.add(&b)?;
                     self.push(result);
                 }
@@ -221,8 +535,14 @@ impl VM {
                     self.call_function(arity)?;
                 }
 
+                OpCode::CallNamed(positional, names) => {
+                    self.call_function_named(positional, &names)?;
+                }
+
                 OpCode::Return => {
                     if let Some(frame) = self.call_stack.pop() {
+                        self.module = frame.return_module;
+
                         // Restore the previous call frame
                         let return_value = self.pop()?;
 
@@ -234,6 +554,10 @@ impl VM {
 
                         // Return to the caller
                         self.ip = frame.return_address;
+
+                        if stop_depth == Some(self.call_stack.len()) {
+                            break;
+                        }
                     } else {
                         // Top-level return, halt execution
                         break;
@@ -254,6 +578,16 @@ impl VM {
         Ok(())
     }
 
+    /// Let the debug hook look at the VM before the next instruction
+    fn debug_check(&mut self) -> JingResult<()> {
+        let Some(mut hook) = self.debug_hook.take() else {
+            return Ok(());
+        };
+        let result = hook.before_instruction(self);
+        self.debug_hook = Some(hook);
+        result
+    }
+
     fn call_function(&mut self, arity: usize) -> JingResult<()> {
         let function = self.peek_at(0)?; // Get function from top of stack
 
@@ -262,40 +596,35 @@ impl VM {
                 name,
                 arity: expected_arity,
                 chunk_start,
+                module,
             } => {
-                if arity != expected_arity {
+                if !expected_arity.accepts(arity) {
                     return Err(JingError::runtime_error(format!(
                         "Function '{}' expects {} arguments, got {}",
                         name, expected_arity, arity
                     )));
                 }
 
-                // Get function info to access parameter names
-                let func_info = self.chunk.functions.get(&name).cloned();
-
-                // Create local environment for this function call
-                let mut local_env = Environment::new();
-                if let Some(func_info) = func_info {
-                    // Bind arguments to parameter names in local environment
-                    let args = self.get_function_args(arity);
-                    for (i, param_name) in func_info.locals.iter().enumerate() {
-                        if i < arity {
-                            local_env.define(param_name.clone(), args[i].clone());
-                        }
-                    }
-                }
+                // Bind arguments to parameter names in a fresh local environment
+                let args = self.get_function_args(arity);
+                let local_env = match self.modules[module].chunk.functions.get(&name) {
+                    Some(func_info) => bind_positional(func_info, args),
+                    None => Environment::new(),
+                };
 
                 // Create a new call frame
                 let frame = CallFrame {
                     function_name: name.clone(),
                     return_address: self.ip,
+                    return_module: self.module,
                     stack_base: self.stack.len() - arity - 1, // -1 for the function itself
                     locals: local_env,
                 };
 
                 self.call_stack.push(frame);
 
-                // Jump to the function's code
+                // Jump to the function's code, in the module that defined it
+                self.module = module;
                 self.ip = chunk_start;
 
                 // Remove the function and arguments from the stack
@@ -305,7 +634,7 @@ impl VM {
                 }
             }
             Value::BuiltinFunction { name, function } => {
-                if arity != function.arity() {
+                if !function.arity().accepts(arity) {
                     return Err(JingError::runtime_error(format!(
                         "Builtin function '{}' expects {} arguments, got {}",
                         name,
@@ -318,7 +647,8 @@ impl VM {
                 let args = self.get_function_args(arity);
 
                 // Call the builtin function
-                let result = function.call(args)?;
+                let result = function.call_with_context(args, &mut CallContext::new(self))?;
+                self.track_allocation(result.heap_size())?;
 
                 // Remove the function and arguments from the stack
                 for _ in 0..=arity {
@@ -336,6 +666,208 @@ impl VM {
         Ok(())
     }
 
+    /// Call the function on top of the stack with `positional` arguments
+    /// followed by one argument for each of `names`
+    fn call_function_named(&mut self, positional: usize, names: &[String]) -> JingResult<()> {
+        let arity = positional + names.len();
+        let (name, chunk_start, module) = match self.peek_at(0)? {
+            Value::Function {
+                name,
+                chunk_start,
+                module,
+                ..
+            } => (name, chunk_start, module),
+            Value::BuiltinFunction { name, .. } => {
+                return Err(JingError::runtime_error(format!(
+                    "Builtin function '{}' does not accept named arguments",
+                    name
+                )));
+            }
+            _ => return Err(JingError::runtime_error("Can only call functions")),
+        };
+
+        let func_info = self.modules[module]
+            .chunk
+            .functions
+            .get(&name)
+            .cloned()
+            .ok_or_else(|| JingError::runtime_error(format!("Unknown function '{}'", name)))?;
+        let params = func_info.fixed_params();
+
+        if func_info.arity.max.is_some() && positional > params.len() {
+            return Err(JingError::runtime_error(format!(
+                "Function '{}' takes {} positional arguments, got {}",
+                name,
+                params.len(),
+                positional
+            )));
+        }
+
+        let mut args = self.get_function_args(arity);
+        let named_values = args.split_off(positional);
+        let mut local_env = bind_positional(&func_info, args);
+
+        for (param_name, value) in names.iter().zip(named_values) {
+            if !params.contains(param_name) {
+                return Err(JingError::runtime_error(format!(
+                    "Function '{}' has no parameter named '{}'",
+                    name, param_name
+                )));
+            }
+            if local_env.get(param_name).is_ok() {
+                return Err(JingError::runtime_error(format!(
+                    "Function '{}' got multiple values for argument '{}'",
+                    name, param_name
+                )));
+            }
+            local_env.define(param_name.clone(), value);
+        }
+
+        // Required parameters come first, so only those need checking
+        if let Some(missing) = params[..func_info.arity.min]
+            .iter()
+            .find(|param_name| local_env.get(param_name).is_err())
+        {
+            return Err(JingError::runtime_error(format!(
+                "Function '{}' missing argument '{}'",
+                name, missing
+            )));
+        }
+
+        self.call_stack.push(CallFrame {
+            function_name: name,
+            return_address: self.ip,
+            return_module: self.module,
+            stack_base: self.stack.len() - arity - 1, // -1 for the function itself
+            locals: local_env,
+        });
+        self.module = module;
+        self.ip = chunk_start;
+
+        for _ in 0..=arity {
+            self.stack.pop();
+        }
+
+        Ok(())
+    }
+
+    /// Load the module for `import "spec"`, returning its exports
+    ///
+    /// A module runs once, on its first import; later imports share its
+    /// exports. Importing a module that is still running is an error.
+    fn import(&mut self, spec: &str) -> JingResult<Value> {
+        // Bundled `std/...` modules take precedence over files
+        let (path, bundled) = match module::bundled(spec) {
+            Some((name, source)) => (PathBuf::from(name), Some(source)),
+            None => {
+                let path = self.find_module(spec)?;
+                (fs::canonicalize(&path).unwrap_or(path), None)
+            }
+        };
+
+        if let Some(loaded) = self.modules.iter().find(|m| m.path.as_ref() == Some(&path)) {
+            return loaded.namespace.clone().ok_or_else(|| {
+                JingError::runtime_error(format!("Circular import of module '{}'", spec))
+            });
+        }
+
+        let source = match bundled {
+            Some(source) => source.to_string(),
+            None => fs::read_to_string(&path).map_err(|err| {
+                JingError::io_error(format!("Could not read module '{}': {}", spec, err))
+            })?,
+        };
+        let chunk = compile_module(&source)
+            .map_err(|err| JingError::compile_error(format!("In module '{}': {}", spec, err)))?;
+
+        let index = self.modules.len();
+        self.modules.push(Module {
+            path: Some(path),
+            chunk: Module::link(chunk, index),
+            globals: Environment::new(),
+            namespace: None,
+        });
+
+        // Run the module's top-level code on its own, then resume the importer
+        let saved_ip = self.ip;
+        let saved_module = self.module;
+        let saved_frames = std::mem::take(&mut self.call_stack);
+        let stack_base = self.stack.len();
+        // The module's top-level code counts as called from the import
+        let saved_depth = self.hidden_depth;
+        self.hidden_depth += saved_frames.len() + 1;
+
+        self.module = index;
+        self.ip = 0;
+        let result = self.run();
+
+        self.ip = saved_ip;
+        self.module = saved_module;
+        self.call_stack = saved_frames;
+        self.hidden_depth = saved_depth;
+        self.stack.truncate(stack_base);
+
+        if let Err(err) = result {
+            // Forget the failed module (and anything it loaded) so it can be retried
+            self.modules.truncate(index);
+            return Err(err);
+        }
+
+        let namespace = self.modules[index].exports();
+        self.modules[index].namespace = Some(namespace.clone());
+        Ok(namespace)
+    }
+
+    /// Find the file for `import "spec"` among the paths scripts may read
+    ///
+    /// Paths the sandbox denies are never probed, so a denied import fails
+    /// the same way whether or not the file exists. `JING_PATH` is read
+    /// the way `env()` reads it, and ignored when environment access is
+    /// denied.
+    fn find_module(&self, spec: &str) -> JingResult<PathBuf> {
+        let from = self.modules[self.module].path.as_deref();
+        let search_path = match self.capabilities.check_env() {
+            Ok(()) => self.env_var(module::JING_PATH),
+            Err(_) => None,
+        };
+        let mut denied = None;
+        for candidate in module::candidates(spec, from, search_path.as_deref()) {
+            match self.capabilities.check_read(&candidate.to_string_lossy()) {
+                Ok(path) if path.is_file() => return Ok(path),
+                Ok(_) => {}
+                Err(err) => {
+                    denied.get_or_insert(err);
+                }
+            }
+        }
+        Err(denied
+            .unwrap_or_else(|| JingError::runtime_error(format!("Cannot find module '{}'", spec))))
+    }
+
+    /// Record an allocation of `bytes`, failing if the memory limit is exceeded
+    ///
+    /// A refused allocation is not counted.
+    fn track_allocation(&mut self, bytes: usize) -> JingResult<()> {
+        self.check_allocation(bytes)?;
+        self.memory_used = self.memory_used.saturating_add(bytes);
+        Ok(())
+    }
+
+    /// Fail if allocating `bytes` more would exceed the memory limit
+    ///
+    /// Builtins that build large values check this before allocating; the
+    /// bytes are only counted once the value exists.
+    pub fn check_allocation(&self, bytes: usize) -> JingResult<()> {
+        let requested = self.memory_used.saturating_add(bytes);
+        match self.memory_limit {
+            Some(limit) if requested > limit => Err(JingError::resource_exhausted(format!(
+                "memory limit of {} bytes exceeded ({} bytes requested)",
+                limit, requested
+            ))),
+            _ => Ok(()),
+        }
+    }
+
     /// Extract function arguments from the stack
     /// Arguments are arranged as: [..., arg0, arg1, ..., argN, function]
     fn get_function_args(&self, arity: usize) -> Vec<Value> {
@@ -385,9 +917,92 @@ impl VM {
         &self.stack
     }
 
+    /// Source line of the statement the next instruction starts, if any
+    ///
+    /// Debuggers use this to stop once per statement rather than once per
+    /// instruction. It is `None` when the chunk has no line table.
+    pub fn line_start(&self) -> Option<usize> {
+        self.modules[self.module].chunk.line_starting_at(self.ip)
+    }
+
+    /// Number of function calls in progress, counting those of scripts
+    /// waiting on an import
+    pub fn call_depth(&self) -> usize {
+        self.hidden_depth + self.call_stack.len()
+    }
+
+    /// Whether the running code belongs to the main script rather than an
+    /// imported module
+    pub fn in_main_script(&self) -> bool {
+        self.module == 0
+    }
+
+    /// The call stack, outermost frame first
+    ///
+    /// The first frame is the top-level code of the running module. While a
+    /// module is being imported, only its own frames are listed.
+    pub fn frames(&self) -> Vec<Frame> {
+        // Each frame runs in the module its callee returns to, and is at
+        // the line of that call; the innermost frame is at `ip`
+        let functions = std::iter::once(None).chain(self.call_stack.iter().map(Some));
+        let mut frames = Vec::with_capacity(self.call_stack.len() + 1);
+        for (index, function) in functions.enumerate() {
+            let (module, address) = match self.call_stack.get(index) {
+                Some(callee) => (
+                    callee.return_module,
+                    callee.return_address.saturating_sub(1),
+                ),
+                None => (self.module, self.ip),
+            };
+            let locals = function.map_or_else(Vec::new, |frame| {
+                frame
+                    .locals
+                    .names()
+                    .into_iter()
+                    .filter_map(|name| frame.locals.get(&name).ok().map(|value| (name, value)))
+                    .collect()
+            });
+            frames.push(Frame {
+                function: function.map(|frame| frame.function_name.clone()),
+                module: self.module_path(module),
+                line: self.modules[module].chunk.line_at(address),
+                locals,
+            });
+        }
+        frames
+    }
+
+    /// Globals of the module whose code is running, sorted by name
+    pub fn current_globals(&self) -> Vec<(String, Value)> {
+        let globals = &self.modules[self.module].globals;
+        globals
+            .names()
+            .into_iter()
+            .filter_map(|name| globals.get(&name).ok().map(|value| (name, value)))
+            .collect()
+    }
+
+    /// File of an imported module, or `None` for the main script
+    fn module_path(&self, module: usize) -> Option<PathBuf> {
+        if module == 0 {
+            return None;
+        }
+        self.modules[module].path.clone()
+    }
+
     /// Get a global variable by name
     pub fn get_global(&self, name: &str) -> Option<Value> {
-        self.globals.get(name).ok()
+        self.modules[0].globals.get(name).ok()
+    }
+
+    /// Names of all global variables and functions, sorted alphabetically
+    pub fn global_names(&self) -> Vec<String> {
+        self.modules[0].globals.names()
+    }
+
+    /// Define or overwrite a global variable
+    pub fn set_global(&mut self, name: &str, value: Value) {
+        self.modules[0].globals.define(name.to_string(), value);
     }
 
     /// Get the top value from the stack (result of last expression)
@@ -401,83 +1016,50 @@ impl VM {
 
     /// Reset the VM state
     pub fn reset(&mut self) {
+        // Imported modules are forgotten too, so they run again when re-imported
+        self.modules.truncate(1);
+        self.modules[0].chunk = Chunk::new();
+        self.modules[0].globals = Environment::new();
+        self.module = 0;
         self.ip = 0;
         self.stack.clear();
-        self.globals = Environment::new();
         self.call_stack.clear();
+        self.hidden_depth = 0;
+        self.memory_used = 0;
     }
 }
 
-/// REPL (Read-Eval-Print Loop) for interactive Jing sessions
-pub struct REPL {
-    vm: VM,
+/// Lex, parse and compile the source of an imported module, with a line
+/// table for stepping through it
+fn compile_module(source: &str) -> JingResult<Chunk> {
+    let tokens = Lexer::new(source).tokenize()?;
+    let mut parser = Parser::new(tokens);
+    let statements = parser.parse()?;
+    let mut compiler = Compiler::new();
+    compiler.set_lines(parser.statement_spans());
+    compiler.compile(statements)
 }
 
-impl REPL {
-    pub fn new() -> Self {
-        REPL { vm: VM::new() }
-    }
-
-    /// Evaluate a single line of Jing code
-    pub fn eval(&mut self, source: &str) -> JingResult<()> {
-        use crate::compiler::Compiler;
-        use crate::lexer::Lexer;
-        use crate::parser::Parser;
-
-        let mut lexer = Lexer::new(source);
-        let tokens = lexer.tokenize()?;
-
-        let mut parser = Parser::new(tokens);
-        let statements = parser.parse()?;
-
-        let mut compiler = Compiler::new();
-        let chunk = compiler.compile(statements)?;
-
-        self.vm.interpret(chunk)
+/// Bind positional arguments to a function's parameters
+///
+/// Extra arguments are collected into the rest parameter, if any. Parameters
+/// with defaults that were not passed stay unbound until the function's
+/// prologue evaluates their default values.
+fn bind_positional(func_info: &FunctionInfo, args: Vec<Value>) -> Environment {
+    let mut locals = Environment::new();
+    let params = func_info.fixed_params();
+    let mut args = args.into_iter();
+
+    for (param_name, arg) in params.iter().zip(args.by_ref()) {
+        locals.define(param_name.clone(), arg);
     }
-
-    /// Start an interactive REPL session
-    pub fn run(&mut self) -> JingResult<()> {
-        use std::io::{self, Write};
-
-        println!("Jing REPL v0.1.0");
-        println!("Type 'exit' to quit.");
-        println!();
-
-        loop {
-            print!("> ");
-            io::stdout().flush().unwrap();
-
-            let mut input = String::new();
-            match io::stdin().read_line(&mut input) {
-                Ok(_) => {
-                    let input = input.trim();
-
-                    if input.is_empty() {
-                        continue;
-                    }
-
-                    if input == "exit" || input == "quit" {
-                        break;
-                    }
-
-                    match self.eval(input) {
-                        Ok(()) => {}
-                        Err(err) => {
-                            eprintln!("Error: {}", err);
-                        }
-                    }
-                }
-                Err(error) => {
-                    eprintln!("Error reading input: {}", error);
-                    break;
-                }
-            }
+    if func_info.arity.max.is_none() {
+        if let Some(rest) = func_info.locals.last() {
+            locals.define(rest.clone(), Value::List(args.collect()));
         }
-
-        println!("Goodbye!");
-        Ok(())
     }
+
+    locals
 }
 
 #[cfg(test)]
@@ -507,7 +1089,7 @@ mod tests {
     fn test_simple_arithmetic() {
         let vm = run_code("let result = 10 + 5;").unwrap();
 
-        let result = vm.globals.get("result").unwrap();
+        let result = vm.get_global("result").unwrap();
         match result {
             Value::Number(n) => assert_eq!(n, 15.0),
             _ => panic!("Expected number result"),
@@ -524,7 +1106,7 @@ mod tests {
         )
         .unwrap();
 
-        let y = vm.globals.get("y").unwrap();
+        let y = vm.get_global("y").unwrap();
         match y {
             Value::Number(n) => assert_eq!(n, 50.0),
             _ => panic!("Expected number result"),
@@ -540,7 +1122,7 @@ mod tests {
         )
         .unwrap();
 
-        let greeting = vm.globals.get("greeting").unwrap();
+        let greeting = vm.get_global("greeting").unwrap();
         match greeting {
             Value::String(s) => assert_eq!(s, "Hello, World!"),
             _ => panic!("Expected string result"),
@@ -0,0 +1,418 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+use jing::cli::{self, Cli, Command as CliCommand, Options};
+use jing::error::JingError;
+
+fn args(list: &[&str]) -> Vec<String> {
+    list.iter().map(|arg| arg.to_string()).collect()
+}
+
+/// Run the jing binary with `list` as arguments
+fn jing(list: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_jing"))
+        .args(list)
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap()
+}
+
+fn write_script(dir: &Path, name: &str, source: &str) -> String {
+    let path = dir.join(name);
+    std::fs::write(&path, source).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn test_parse_commands_and_flags() {
+    let cli = Cli::parse(&args(&[])).unwrap();
+    assert_eq!(cli.command, CliCommand::Repl);
+    assert_eq!(cli.options, Options::default());
+
+    let cli = Cli::parse(&args(&["--quiet", "--no-color", "run", "main.jing"])).unwrap();
+    assert_eq!(
+        cli.command,
+        CliCommand::Run {
+            path: "main.jing".to_string(),
+            args: vec![]
+        }
+    );
+    assert!(cli.options.quiet);
+    assert!(!cli.options.color);
+
+    // A bare script path still runs it
+    let cli = Cli::parse(&args(&["hello.jing"])).unwrap();
+    assert!(matches!(cli.command, CliCommand::Run { .. }));
+
+    let cli = Cli::parse(&args(&["check", "--help"])).unwrap();
+    assert_eq!(
+        cli.command,
+        CliCommand::Help {
+            command: Some("check".to_string())
+        }
+    );
+
+    // Help before a script path is general help, not an unknown command
+    let cli = Cli::parse(&args(&["-h", "hello.jing"])).unwrap();
+    assert_eq!(cli.command, CliCommand::Help { command: None });
+
+    assert!(Cli::parse(&args(&["--bogus"])).is_err());
+    assert!(Cli::parse(&args(&["frobnicate"])).is_err());
+    // A directory is not a script, so it is reported as an unknown command
+    let dir = env!("CARGO_MANIFEST_DIR");
+    assert_eq!(
+        Cli::parse(&args(&[dir])).unwrap_err(),
+        format!("unknown command '{}'", dir)
+    );
+    assert!(Cli::parse(&args(&["run"])).is_err());
+    assert!(Cli::parse(&args(&["check"])).is_err());
+}
+
+#[test]
+fn test_parse_forwards_script_arguments() {
+    // Everything after the script path belongs to the script, flags included
+    let cli = Cli::parse(&args(&["tool.jing", "a", "--quiet", "-h"])).unwrap();
+    assert_eq!(
+        cli.command,
+        CliCommand::Run {
+            path: "tool.jing".to_string(),
+            args: args(&["a", "--quiet", "-h"])
+        }
+    );
+    assert_eq!(cli.options, Options::default());
+
+    let cli = Cli::parse(&args(&["-q", "run", "tool.jing", "run", "b"])).unwrap();
+    assert_eq!(
+        cli.command,
+        CliCommand::Run {
+            path: "tool.jing".to_string(),
+            args: args(&["run", "b"])
+        }
+    );
+    assert!(cli.options.quiet);
+}
+
+#[test]
+fn test_parse_inline_code_and_stdin() {
+    let cli = Cli::parse(&args(&["-q", "-e", "print(1);", "x", "-y"])).unwrap();
+    assert_eq!(
+        cli.command,
+        CliCommand::Eval {
+            source: "print(1);".to_string(),
+            args: args(&["x", "-y"])
+        }
+    );
+    assert!(cli.options.quiet);
+
+    let cli = Cli::parse(&args(&["-", "a"])).unwrap();
+    assert_eq!(
+        cli.command,
+        CliCommand::Run {
+            path: "-".to_string(),
+            args: args(&["a"])
+        }
+    );
+
+    assert!(Cli::parse(&args(&["--eval"])).is_err());
+}
+
+#[test]
+fn test_exit_codes_by_error_kind() {
+    assert_eq!(JingError::parse_error("x", 1).exit_code(), 65);
+    assert_eq!(JingError::compile_error("x").exit_code(), 65);
+    assert_eq!(JingError::runtime_error("x").exit_code(), 70);
+    assert_eq!(JingError::type_error("x").exit_code(), 70);
+    assert_eq!(JingError::io_error("x").exit_code(), 74);
+    assert_eq!(JingError::permission_denied("x").exit_code(), 77);
+    assert_eq!(JingError::exit(3).exit_code(), 3);
+    assert_eq!(cli::main(&args(&["--bogus"])), cli::EXIT_USAGE);
+}
+
+#[test]
+fn test_run_and_check_commands() {
+    let dir = tempfile::tempdir().unwrap();
+    let good = write_script(dir.path(), "good.jing", "print(1 + 2);");
+    let syntax = write_script(dir.path(), "syntax.jing", "let = ;");
+    let runtime = write_script(dir.path(), "runtime.jing", "missing();");
+
+    let output = jing(&["run", &good]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "3\n");
+
+    assert_eq!(jing(&[&good]).status.code(), Some(0));
+    assert_eq!(jing(&["run", &syntax]).status.code(), Some(65));
+    assert_eq!(jing(&["run", &runtime]).status.code(), Some(70));
+    assert_eq!(jing(&["run", "/no/such/file.jing"]).status.code(), Some(66));
+
+    let output = jing(&["check", &good, &syntax]);
+    assert_eq!(output.status.code(), Some(65));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("good.jing: ok"));
+    assert!(stderr.contains("error: "));
+
+    // Checking does not run the script
+    let output = jing(&["--quiet", "check", &runtime]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_script_arguments_environment_and_exit() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = write_script(
+        dir.path(),
+        "tool.jing",
+        "let a = args();\n\
+         print(len(a), get(a, 0), get(a, 2));\n\
+         print(env(\"JING_TEST_GREETING\"), env(\"JING_TEST_UNSET\"));\n\
+         set_env(\"JING_TEST_GREETING\", \"bye\");\n\
+         print(env(\"JING_TEST_GREETING\"));\n\
+         if (len(a) > 2) { exit(3); }\n\
+         print(\"not reached\");",
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_jing"))
+        .args([script.as_str(), "a", "--flag", "c"])
+        .env("NO_COLOR", "1")
+        .env("JING_TEST_GREETING", "hi")
+        .env_remove("JING_TEST_UNSET")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "3 a c\nhi nil\nbye\n"
+    );
+    // Exiting is not reported as an error
+    assert!(output.stderr.is_empty());
+
+    let exit_zero = write_script(dir.path(), "zero.jing", "exit(); print(1);");
+    let output = jing(&[&exit_zero]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+
+    let bad = write_script(dir.path(), "bad.jing", "exit(300);");
+    assert_eq!(jing(&[&bad]).status.code(), Some(70));
+}
+
+#[test]
+fn test_inline_stdin_and_shebang_scripts() {
+    let output = jing(&["-e", "print(1 + 2, len(args()));", "a", "b"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "3 2\n");
+    assert_eq!(jing(&["-e", "let = ;"]).status.code(), Some(65));
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jing"))
+        .args(["-", "piped"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"print(get(args(), 0));")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "piped\n");
+
+    let dir = tempfile::tempdir().unwrap();
+    let script = write_script(
+        dir.path(),
+        "tool.jing",
+        "#!/usr/bin/env jing\nprint(\"shebang\");",
+    );
+    let output = jing(&[&script]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "shebang\n");
+}
+
+#[test]
+fn test_disasm_and_help_commands() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = write_script(
+        dir.path(),
+        "f.jing",
+        "fn double(x) { return x * 2; } print(double(21));",
+    );
+
+    let output = jing(&["disasm", &script]);
+    assert_eq!(output.status.code(), Some(0));
+    let listing = String::from_utf8_lossy(&output.stdout);
+    assert!(listing.contains("<fn double(x)>:"));
+    assert!(listing.contains("Constant 0 ; 2"));
+    assert!(listing.contains("Call 1"));
+
+    let output = jing(&["--help"]);
+    assert_eq!(output.status.code(), Some(0));
+    let help = String::from_utf8_lossy(&output.stdout);
+    for command in [
+        "run", "repl", "check", "disasm", "debug", "fmt", "test", "lsp",
+    ] {
+        assert!(help.contains(command), "help should mention {}", command);
+    }
+    // Descriptions start in one column, past the longest usage
+    let column = help.find("Run a script").unwrap() - help.find("  run <file>").unwrap();
+    for description in ["Run test_* functions", "Show the version"] {
+        let start = help.find(description).unwrap();
+        let line = help[..start].rfind('\n').unwrap() + 1;
+        assert_eq!(start - line, column, "{} is misaligned", description);
+    }
+
+    assert_eq!(jing(&["help", "nope"]).status.code(), Some(64));
+    let version = jing(&["--version"]);
+    assert!(String::from_utf8_lossy(&version.stdout).starts_with("jing "));
+}
+
+#[test]
+fn test_parse_test_command() {
+    let cli = Cli::parse(&args(&["test", "tests", "--filter", "parse"])).unwrap();
+    assert_eq!(
+        cli.command,
+        CliCommand::Test {
+            paths: vec!["tests".to_string()],
+            filter: Some("parse".to_string())
+        }
+    );
+
+    assert!(Cli::parse(&args(&["test", "--filter"])).is_err());
+    assert!(Cli::parse(&args(&["run", "--filter", "x"])).is_err());
+}
+
+#[test]
+fn test_parse_debug_command() {
+    let cli = Cli::parse(&args(&["debug", "main.jing", "--verbose"])).unwrap();
+    assert_eq!(
+        cli.command,
+        CliCommand::Debug {
+            path: "main.jing".to_string(),
+            args: vec!["--verbose".to_string()]
+        }
+    );
+
+    assert!(Cli::parse(&args(&["debug"])).is_err());
+    assert!(Cli::parse(&args(&["debug", "-"])).is_err());
+}
+
+#[test]
+fn test_debug_command_reads_commands_from_stdin() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = write_script(
+        dir.path(),
+        "main.jing",
+        "fn double(x) {\n    return x * 2;\n}\nlet y = double(4);\nprint(y);\n",
+    );
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jing"))
+        .args(["debug", &script])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"b 2\nc\np x\nc\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Stopped at line 1\n"), "{}", stdout);
+    assert!(stdout.contains("Stopped at line 2 in double()\n"));
+    assert!(stdout.contains("x = 4\n"));
+    assert!(stdout.ends_with("8\n"));
+}
+
+#[test]
+fn test_debug_command_shares_stdin_with_input() {
+    let dir = tempfile::tempdir().unwrap();
+    let script = write_script(
+        dir.path(),
+        "main.jing",
+        "let name = input(\"name? \");\nprint(\"hi \" + name);\n",
+    );
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jing"))
+        .args(["debug", &script])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // The debugger reads `n` and `c`; the script reads the line between
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"n\nBob\nc\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Stopped at line 2\n"), "{}", stdout);
+    assert!(stdout.ends_with("hi Bob\n"), "{}", stdout);
+}
+
+#[test]
+fn test_parse_lsp_command() {
+    let cli = Cli::parse(&args(&["lsp"])).unwrap();
+    assert_eq!(cli.command, CliCommand::Lsp);
+    assert!(Cli::parse(&args(&["lsp", "extra"])).is_err());
+}
+
+#[test]
+fn test_test_command_reports_failures() {
+    let dir = tempfile::tempdir().unwrap();
+    write_script(
+        dir.path(),
+        "sample_test.jing",
+        r#"
+        fn test_passes() { assert_eq(1 + 1, 2); }
+        fn test_fails() { assert_eq(range(3), range(4)); }
+        "#,
+    );
+    let root = dir.path().to_string_lossy().into_owned();
+
+    let output = jing(&["test", &root]);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("test_passes ... ok"));
+    assert!(stdout.contains("test_fails ... FAILED"));
+    assert!(stdout.contains("diff: lengths differ: 3 vs 4"));
+    assert!(stdout.contains("test result: FAILED. 1 passed; 1 failed; 0 filtered out"));
+
+    let output = jing(&["test", &root, "--filter", "passes"]);
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("test result: ok. 1 passed; 0 failed; 1 filtered out"));
+}
+
+#[test]
+fn test_fmt_command_rewrites_and_checks() {
+    let dir = tempfile::tempdir().unwrap();
+    let messy = write_script(dir.path(), "messy.jing", "let x=1+2;\nif x>2{print(x);}\n");
+    let tidy = write_script(dir.path(), "tidy.jing", "let y = 1;\n");
+
+    let output = jing(&["fmt", "--check", &messy, &tidy]);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("messy.jing: not formatted"));
+    assert!(!stdout.contains("tidy.jing"));
+
+    assert_eq!(jing(&["fmt", &messy]).status.code(), Some(0));
+    assert_eq!(
+        std::fs::read_to_string(&messy).unwrap(),
+        "let x = 1 + 2;\nif x > 2 {\n    print(x);\n}\n"
+    );
+    assert_eq!(jing(&["fmt", "--check", &messy]).status.code(), Some(0));
+
+    let broken = write_script(dir.path(), "broken.jing", "let = ;");
+    assert_eq!(jing(&["fmt", &broken]).status.code(), Some(65));
+    assert!(Cli::parse(&args(&["run", "x.jing", "--check"])).is_ok());
+    assert!(Cli::parse(&args(&["check", "--check", "x.jing"])).is_err());
+}
@@ -428,3 +428,28 @@ fn test_compile_logical_operators() {
         assert_eq!(result, expected, "Failed for input: {}", input);
     }
 }
+
+#[test]
+fn test_vm_memory_accounting() {
+    let input = r#"
+        let greeting = "Hello, " + "World!";
+        let total = 1 + 2;
+    "#;
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let statements = parser.parse().unwrap();
+    let mut compiler = Compiler::new();
+    let chunk = compiler.compile(statements).unwrap();
+
+    let mut vm = VM::new();
+    vm.set_memory_limit(Some(1024));
+    vm.interpret(chunk).unwrap();
+
+    // Only the string concatenation allocates
+    assert_eq!(vm.memory_used(), "Hello, World!".len());
+    assert_eq!(vm.memory_limit(), Some(1024));
+
+    vm.reset();
+    assert_eq!(vm.memory_used(), 0);
+}
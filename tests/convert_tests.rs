@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use jing::convert::{arg, optional_arg, FromValue, IntoValue};
+use jing::error::JingError;
+use jing::value::Value;
+
+fn type_error_message<T: FromValue + std::fmt::Debug>(value: Value) -> String {
+    match T::from_value(value).unwrap_err() {
+        JingError::TypeError { message } => message,
+        other => panic!("Expected TypeError, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_scalar_conversions() {
+    assert_eq!(f64::from_value(Value::Number(1.5)).unwrap(), 1.5);
+    assert_eq!(i64::from_value(Value::Number(7.0)).unwrap(), 7);
+    assert!(bool::from_value(Value::Bool(true)).unwrap());
+    assert_eq!(
+        String::from_value(Value::String("hi".to_string())).unwrap(),
+        "hi"
+    );
+
+    assert_eq!(2.5.into_value(), Value::Number(2.5));
+    assert_eq!(3i64.into_value(), Value::Number(3.0));
+    assert_eq!(false.into_value(), Value::Bool(false));
+    assert_eq!("text".into_value(), Value::String("text".to_string()));
+    assert_eq!(().into_value(), Value::Nil);
+}
+
+#[test]
+fn test_scalar_conversion_errors() {
+    assert_eq!(
+        type_error_message::<f64>(Value::String("x".to_string())),
+        "expected number, got string"
+    );
+    assert_eq!(
+        type_error_message::<i64>(Value::Number(1.5)),
+        "expected integer, got 1.5"
+    );
+    assert_eq!(
+        type_error_message::<bool>(Value::Nil),
+        "expected bool, got nil"
+    );
+}
+
+#[test]
+fn test_option_conversions() {
+    assert_eq!(Option::<f64>::from_value(Value::Nil).unwrap(), None);
+    assert_eq!(
+        Option::<f64>::from_value(Value::Number(1.0)).unwrap(),
+        Some(1.0)
+    );
+    assert_eq!(
+        type_error_message::<Option<f64>>(Value::Bool(true)),
+        "expected number or nil, got bool"
+    );
+    assert_eq!(None::<f64>.into_value(), Value::Nil);
+    assert_eq!(Some("a").into_value(), Value::String("a".to_string()));
+}
+
+#[test]
+fn test_collection_conversions() {
+    let list = vec![1.0, 2.0, 3.0].into_value();
+    assert_eq!(
+        list,
+        Value::List(vec![
+            Value::Number(1.0),
+            Value::Number(2.0),
+            Value::Number(3.0)
+        ])
+    );
+    assert_eq!(Vec::<f64>::from_value(list).unwrap(), vec![1.0, 2.0, 3.0]);
+
+    let mixed = Value::List(vec![Value::Number(1.0), Value::Bool(true)]);
+    assert_eq!(
+        type_error_message::<Vec<f64>>(mixed),
+        "list element 1 expected number, got bool"
+    );
+    assert_eq!(
+        type_error_message::<Vec<f64>>(Value::Nil),
+        "expected list of number, got nil"
+    );
+
+    let mut scores = HashMap::new();
+    scores.insert("alice".to_string(), 3i64);
+    let map = scores.clone().into_value();
+    assert_eq!(map.type_name(), "map");
+    assert_eq!(HashMap::<String, i64>::from_value(map).unwrap(), scores);
+}
+
+#[test]
+fn test_builtin_argument_errors() {
+    let args = vec![Value::Number(1.0), Value::Nil];
+    assert_eq!(arg::<f64>("f", &args, 0).unwrap(), 1.0);
+    match arg::<String>("f", &args, 1).unwrap_err() {
+        JingError::TypeError { message } => {
+            assert_eq!(message, "f() argument 2 expected string, got nil")
+        }
+        other => panic!("Expected TypeError, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_missing_arguments() {
+    let args = vec![Value::Number(1.0)];
+
+    // Option parameters still need an argument unless they opt in
+    match arg::<Option<f64>>("f", &args, 1).unwrap_err() {
+        JingError::RuntimeError { message } => assert_eq!(message, "f() missing argument 2"),
+        other => panic!("Expected RuntimeError, got {:?}", other),
+    }
+
+    assert_eq!(optional_arg::<f64>("f", &args, 0).unwrap(), Some(1.0));
+    assert_eq!(optional_arg::<f64>("f", &args, 1).unwrap(), None);
+    assert_eq!(optional_arg::<f64>("f", &[Value::Nil], 0).unwrap(), None);
+    assert!(optional_arg::<f64>("f", &[Value::Bool(true)], 0).is_err());
+}
@@ -0,0 +1,235 @@
+use std::cell::RefCell;
+use std::io::{Cursor, Write};
+use std::rc::Rc;
+
+use jing::cli;
+use jing::debugger::Debugger;
+use jing::error::{JingError, JingResult};
+use jing::value::Value;
+use jing::vm::VM;
+
+const SCRIPT: &str = "\
+fn area(width, height = 1) {
+    let result = width * height;
+    return result;
+}
+
+let total = 0;
+let i = 0;
+while i < 2 {
+    total = total + area(i, 3);
+    i = i + 1;
+}
+print(total);
+";
+
+/// Output shared with a debugger owned by the VM
+#[derive(Clone, Default)]
+struct Output(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Debug `source` with scripted `commands`, returning the result and what
+/// the debugger printed
+fn debug(source: &str, breakpoints: &[usize], commands: &str) -> (JingResult<()>, String) {
+    let output = Output::default();
+    let mut debugger = Debugger::new(source, Cursor::new(commands.to_string()), output.clone());
+    for line in breakpoints {
+        debugger.set_breakpoint(*line);
+    }
+
+    let mut vm = VM::new();
+    vm.set_debug_hook(Some(Box::new(debugger)));
+    let result = vm.interpret(cli::compile(source).unwrap());
+    let text = String::from_utf8(output.0.borrow().clone()).unwrap();
+    (result, text)
+}
+
+/// The lines the debugger stopped at, in order
+fn stops(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split("Stopped at ").nth(1))
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn test_line_table_maps_addresses_to_statements() {
+    let chunk = cli::compile(SCRIPT).unwrap();
+    let lines: Vec<usize> = chunk.lines.iter().map(|(_, line)| *line).collect();
+    assert_eq!(lines, vec![1, 2, 3, 6, 7, 8, 9, 10, 12]);
+
+    let addresses: Vec<usize> = chunk.lines.iter().map(|(address, _)| *address).collect();
+    assert!(addresses.windows(2).all(|pair| pair[0] < pair[1]));
+    for (address, line) in &chunk.lines {
+        assert_eq!(chunk.line_starting_at(*address), Some(*line));
+        assert_eq!(chunk.line_at(*address), Some(*line));
+    }
+    assert_eq!(chunk.line_starting_at(addresses[0] + 1), None);
+    assert_eq!(chunk.line_at(addresses[0] + 1), Some(1));
+
+    // Code compiled without the parser's spans has no line table
+    let tokens = jing::Lexer::new(SCRIPT).tokenize().unwrap();
+    let statements = jing::Parser::new(tokens).parse().unwrap();
+    let chunk = jing::Compiler::new().compile(statements).unwrap();
+    assert!(chunk.lines.is_empty());
+    assert_eq!(chunk.line_at(0), None);
+}
+
+#[test]
+fn test_hook_runs_before_each_instruction() {
+    let chunk = cli::compile(SCRIPT).unwrap();
+    let instructions = Rc::new(RefCell::new(0));
+    let statements = Rc::new(RefCell::new(Vec::new()));
+
+    let mut vm = VM::new();
+    let (count, seen) = (instructions.clone(), statements.clone());
+    vm.set_debug_hook(Some(Box::new(move |vm: &VM| -> JingResult<()> {
+        *count.borrow_mut() += 1;
+        if let Some(line) = vm.line_start() {
+            seen.borrow_mut().push((line, vm.call_depth()));
+        }
+        Ok(())
+    })));
+    vm.interpret(chunk.clone()).unwrap();
+
+    assert!(*instructions.borrow() > statements.borrow().len());
+    assert_eq!(
+        *statements.borrow(),
+        vec![
+            (1, 0),
+            (6, 0),
+            (7, 0),
+            (8, 0),
+            (9, 0),
+            (2, 1),
+            (3, 1),
+            (10, 0),
+            (8, 0),
+            (9, 0),
+            (2, 1),
+            (3, 1),
+            (10, 0),
+            (8, 0),
+            (12, 0),
+        ]
+    );
+
+    // An error from the hook stops the script
+    vm.reset();
+    vm.set_debug_hook(Some(Box::new(|vm: &VM| match vm.line_start() {
+        Some(9) => Err(JingError::runtime_error("stopped")),
+        _ => Ok(()),
+    })));
+    assert!(vm.interpret(chunk).is_err());
+    assert_eq!(vm.get_global("total"), Some(Value::Number(0.0)));
+}
+
+#[test]
+fn test_frames_show_locals_and_call_lines() {
+    let frames = Rc::new(RefCell::new(Vec::new()));
+    let seen = frames.clone();
+    let mut vm = VM::new();
+    vm.set_debug_hook(Some(Box::new(move |vm: &VM| -> JingResult<()> {
+        if vm.line_start() == Some(3) && seen.borrow().is_empty() {
+            *seen.borrow_mut() = vm.frames();
+        }
+        Ok(())
+    })));
+    vm.interpret(cli::compile(SCRIPT).unwrap()).unwrap();
+
+    let frames = frames.borrow();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].function, None);
+    assert_eq!(frames[0].line, Some(9));
+    assert!(frames[0].locals.is_empty());
+    assert_eq!(frames[1].function.as_deref(), Some("area"));
+    assert_eq!(frames[1].line, Some(3));
+    assert_eq!(frames[1].module, None);
+    assert_eq!(
+        frames[1].locals,
+        vec![
+            ("height".to_string(), Value::Number(3.0)),
+            ("width".to_string(), Value::Number(0.0)),
+        ]
+    );
+}
+
+#[test]
+fn test_step_into_over_and_out() {
+    // Stops at the first statement, then `step` enters the call on line 9
+    let (result, output) = debug(SCRIPT, &[], "n\nn\nn\nn\ns\ns\no\nq\n");
+    assert!(matches!(result, Err(JingError::Exit { code: 0 })));
+    assert_eq!(
+        stops(&output),
+        vec![
+            "line 1",
+            "line 6",
+            "line 7",
+            "line 8",
+            "line 9",
+            "line 2 in area()",
+            "line 3 in area()",
+            "line 10",
+        ]
+    );
+
+    // `next` runs calls through
+    let (_, output) = debug(SCRIPT, &[], "n\nn\nn\nn\nn\nn\nq\n");
+    assert_eq!(
+        stops(&output)[4..],
+        ["line 9", "line 10", "line 8"].map(String::from)
+    );
+}
+
+#[test]
+fn test_breakpoints_and_inspection() {
+    let commands = "c\nlocals\np width\np total\nbt\nstack\nd 2\nb 12\nb\nc\nglobals\nc\n";
+    let (result, output) = debug(SCRIPT, &[2], commands);
+    assert!(result.is_ok());
+    assert_eq!(
+        stops(&output),
+        vec!["line 1", "line 2 in area()", "line 12"]
+    );
+    assert!(output.contains("->    2 |     let result = width * height;"));
+    assert!(output.contains("height = 3\nwidth = 0\n"));
+    assert!(output.contains("width = 0\n(jdb) total = 0\n"));
+    assert!(output.contains("#0 area() at line 2\n#1 <top level> at line 9\n"));
+    // `total` waits on the stack for the call to return
+    assert!(output.contains("(jdb) [0] 0\n(jdb) "));
+    assert!(output.contains("Breakpoint at line 2 removed"));
+    assert!(output.contains("Breakpoint at line 12\n"));
+    assert!(output.contains("total = 3\n"));
+}
+
+#[test]
+fn test_empty_line_repeats_and_end_of_input_quits() {
+    let (result, output) = debug(SCRIPT, &[], "n\n\n\nfoo\n");
+    assert!(matches!(result, Err(JingError::Exit { code: 0 })));
+    assert_eq!(stops(&output), vec!["line 1", "line 6", "line 7", "line 8"]);
+    assert!(output.contains("Unknown command 'foo'"));
+}
+
+#[test]
+fn test_stepping_into_imported_modules() {
+    let source = "import \"std/math\";\nprint(math.sign(-2));\n";
+
+    // Stepping over the import does not stop inside the module
+    let (_, output) = debug(source, &[], "n\nn\nq\n");
+    assert_eq!(stops(&output), vec!["line 1", "line 2"]);
+
+    let (_, output) = debug(source, &[], "n\ns\nbt\nl\nq\n");
+    let stopped = &stops(&output)[2];
+    assert!(stopped.contains("of std/math in sign()"), "{}", stopped);
+    assert!(output.contains("No source for imported modules"));
+}
@@ -209,6 +209,77 @@ fn test_error_display() {
     let display = format!("{}", io_error);
     assert!(display.contains("I/O error"));
     assert!(display.contains("Test I/O error"));
+
+    let resource_error = JingError::resource_exhausted("Test resource error");
+    let display = format!("{}", resource_error);
+    assert!(display.contains("Resource exhausted"));
+    assert!(display.contains("Test resource error"));
+}
+
+#[test]
+fn test_memory_limit_exceeded() {
+    let input = r#"
+        let s = "ab";
+        while (true) {
+            s = s + s;
+        }
+    "#;
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let statements = parser.parse().unwrap();
+    let mut compiler = Compiler::new();
+    let chunk = compiler.compile(statements).unwrap();
+
+    let mut vm = VM::new();
+    vm.set_memory_limit(Some(64 * 1024));
+    let result = vm.interpret(chunk);
+    match result.unwrap_err() {
+        JingError::ResourceExhausted { message } => {
+            assert!(message.contains("memory limit"));
+        }
+        other => panic!("Expected ResourceExhausted, got {:?}", other),
+    }
+    // The refused allocation is not counted
+    assert!(vm.memory_used() <= 64 * 1024);
+}
+
+#[test]
+fn test_memory_limit_checked_before_building_lists() {
+    let tokens = Lexer::new("let big = range(20000000);").tokenize().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    let chunk = Compiler::new().compile(statements).unwrap();
+
+    let mut vm = VM::new();
+    vm.set_memory_limit(Some(1_000_000));
+    match vm.interpret(chunk).unwrap_err() {
+        JingError::ResourceExhausted { message } => {
+            assert!(
+                message.contains("memory limit of 1000000 bytes"),
+                "{}",
+                message
+            );
+        }
+        other => panic!("Expected ResourceExhausted, got {:?}", other),
+    }
+    assert_eq!(vm.memory_used(), 0);
+}
+
+#[test]
+fn test_nested_builtin_callbacks_are_limited() {
+    let tokens = Lexer::new("fn f(n) { return map(range(1), f); } f(0);")
+        .tokenize()
+        .unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    let chunk = Compiler::new().compile(statements).unwrap();
+
+    let mut vm = VM::new();
+    match vm.interpret(chunk).unwrap_err() {
+        JingError::ResourceExhausted { message } => {
+            assert!(message.contains("32 levels deep"), "{}", message);
+        }
+        other => panic!("Expected ResourceExhausted, got {:?}", other),
+    }
 }
 
 #[test]
@@ -0,0 +1,142 @@
+use std::fs;
+
+use jing::formatter::format_source;
+use jing::lexer::Lexer;
+use jing::parser::{Parser, Stmt};
+
+fn format(source: &str) -> String {
+    format_source(source).unwrap()
+}
+
+fn parse(source: &str) -> Vec<Stmt> {
+    let tokens = Lexer::new(source).tokenize().unwrap();
+    Parser::new(tokens).parse().unwrap()
+}
+
+#[test]
+fn test_format_canonical_layout() {
+    let source = "fn add(a,b=1,...rest){let sum=a+b;\nif sum>10{return sum;}else if sum<0 {return 0;} else{}\nwhile sum<5 sum=sum+1;return sum;}";
+    let expected = "\
+fn add(a, b = 1, ...rest) {
+    let sum = a + b;
+    if sum > 10 {
+        return sum;
+    } else if sum < 0 {
+        return 0;
+    } else {}
+    while sum < 5 sum = sum + 1;
+    return sum;
+}
+";
+    assert_eq!(format(source), expected);
+}
+
+#[test]
+fn test_format_parentheses_follow_precedence() {
+    let cases = [
+        ("print((1 + 2) * 3);", "print((1 + 2) * 3);\n"),
+        ("print(((1 * 2)) + 3);", "print(1 * 2 + 3);\n"),
+        ("print(1 - (2 - 3));", "print(1 - (2 - 3));\n"),
+        ("print((1 - 2) - 3);", "print(1 - 2 - 3);\n"),
+        ("let x = not (a and b) or c;", "let x = !(a && b) || c;\n"),
+        ("let y = -(-1);", "let y = -(-1);\n"),
+        ("let z = (f)(1).name;", "let z = f(1).name;\n"),
+        (
+            "show(\"a\\\"b\\n\", port: 80);",
+            "show(\"a\\\"b\\n\", port: 80);\n",
+        ),
+    ];
+    for (source, expected) in cases {
+        let formatted = format(source);
+        assert_eq!(formatted, expected, "formatting {}", source);
+        assert_eq!(parse(&formatted), parse(source), "meaning of {}", source);
+    }
+}
+
+#[test]
+fn test_format_keeps_comments_and_blank_lines() {
+    let source = "#!/usr/bin/env jing
+// Greeting
+
+
+import \"std/strings\" as strings;   // default name
+let name = \"jing\";
+fn greet() {
+  // body
+  print(name);
+
+  // before the closing brace
+}
+// trailer
+";
+    let expected = "#!/usr/bin/env jing
+// Greeting
+
+import \"std/strings\"; // default name
+let name = \"jing\";
+fn greet() {
+    // body
+    print(name);
+
+    // before the closing brace
+}
+// trailer
+";
+    assert_eq!(format(source), expected);
+}
+
+#[test]
+fn test_format_keeps_comments_after_one_line_blocks() {
+    let source =
+        "let x = 2;\nwhile (x) { x = x - 1; } // done\nif (x) { if (x) { print(x); } } // nested\n";
+    let expected = "\
+let x = 2;
+while x {
+    x = x - 1;
+} // done
+if x {
+    if x {
+        print(x);
+    }
+} // nested
+";
+    assert_eq!(format(source), expected);
+    assert_eq!(format(expected), expected);
+
+    // A comment before an `else` on a later line stays with the statement
+    let source = "if (x) { x = 1; } // one\nelse { x = 2; }\n";
+    assert_eq!(
+        format(source),
+        "if x {\n    x = 1; // one\n} else {\n    x = 2;\n}\n"
+    );
+
+    // Bodies without braces are left without them
+    let source = "if x print(1); else print(2); // both\n";
+    assert_eq!(format(source), source);
+}
+
+#[test]
+fn test_format_is_idempotent_on_bundled_sources() {
+    for dir in ["examples", "std"] {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "jing") {
+                let source = fs::read_to_string(&path).unwrap();
+                let formatted = format(&source);
+                assert_eq!(format(&formatted), formatted, "{}", path.display());
+                assert_eq!(parse(&formatted), parse(&source), "{}", path.display());
+                assert_eq!(
+                    formatted.matches("//").count(),
+                    source.matches("//").count(),
+                    "comments lost in {}",
+                    path.display()
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_format_reports_syntax_errors() {
+    assert!(format_source("let = ;").is_err());
+}
@@ -0,0 +1,124 @@
+use std::sync::Arc;
+
+use jing::compiler::Compiler;
+use jing::error::JingResult;
+use jing::features::{Arity, BuiltinFunction, CallContext};
+use jing::lexer::Lexer;
+use jing::parser::Parser;
+use jing::value::Value;
+use jing::vm::VM;
+
+/// Helper function to run Jing code on an existing VM
+fn run_on(vm: &mut VM, code: &str) -> JingResult<Value> {
+    let mut lexer = Lexer::new(code);
+    let tokens = lexer.tokenize()?;
+
+    let mut parser = Parser::new(tokens);
+    let statements = parser.parse()?;
+
+    let mut compiler = Compiler::new();
+    let chunk = compiler.compile(statements)?;
+
+    vm.interpret(chunk)?;
+    vm.get_result()
+}
+
+fn numbers(values: &[f64]) -> Value {
+    Value::List(values.iter().map(|n| Value::Number(*n)).collect())
+}
+
+#[test]
+fn test_map_filter_sort_by() {
+    let mut vm = VM::new();
+    let result = run_on(
+        &mut vm,
+        r#"
+        fn square(x) { return x * x; }
+        fn is_odd(x) { return x % 2 == 1; }
+        fn descending(x) { return -x; }
+        let squares = map(range(5), square);
+        let odd = filter(squares, is_odd);
+        sort_by(odd, descending);
+    "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        vm.get_global("squares"),
+        Some(numbers(&[0.0, 1.0, 4.0, 9.0, 16.0]))
+    );
+    assert_eq!(result, numbers(&[9.0, 1.0]));
+}
+
+#[test]
+fn test_apply_with_builtins_and_functions() {
+    let mut vm = VM::new();
+    let result = run_on(
+        &mut vm,
+        r#"
+        fn add(a, b) { return a + b; }
+        apply(add, map(range(2), sqrt));
+    "#,
+    )
+    .unwrap();
+    assert_eq!(result, Value::Number(1.0));
+}
+
+#[test]
+fn test_callback_errors_propagate() {
+    let mut vm = VM::new();
+    assert!(run_on(
+        &mut vm,
+        r#"fn bad(x) { return x + nil; } map(range(3), bad);"#
+    )
+    .is_err());
+    assert!(run_on(&mut vm, "map(range(3), 42);").is_err());
+    assert!(run_on(&mut vm, "sort_by(range(2), type);").is_ok());
+
+    // Higher-order builtins need a VM to call back into
+    assert!(vm
+        .registry()
+        .call("map", vec![numbers(&[1.0]), Value::Nil])
+        .is_err());
+}
+
+/// A host builtin that reads a global and calls back into Jing code
+#[derive(Debug)]
+struct TwiceFunction;
+
+impl BuiltinFunction for TwiceFunction {
+    fn name(&self) -> &str {
+        "twice"
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exact(1)
+    }
+
+    fn call(&self, _args: Vec<Value>) -> JingResult<Value> {
+        Ok(Value::Nil)
+    }
+
+    fn call_with_context(&self, args: Vec<Value>, context: &mut CallContext) -> JingResult<Value> {
+        let seed = context.get_global("seed").unwrap_or(Value::Number(0.0));
+        let once = context.call(&args[0], vec![seed])?;
+        context.call(&args[0], vec![once])
+    }
+}
+
+#[test]
+fn test_custom_higher_order_builtin() {
+    let mut vm = VM::new();
+    vm.registry_mut().register(Arc::new(TwiceFunction));
+
+    let result = run_on(
+        &mut vm,
+        r#"
+        let seed = 3;
+        fn inc(x) { return x + 1; }
+        twice(inc);
+    "#,
+    )
+    .unwrap();
+    assert_eq!(result, Value::Number(5.0));
+}
@@ -0,0 +1,133 @@
+use jing::compiler::Compiler;
+use jing::error::{JingError, JingResult};
+use jing::lexer::Lexer;
+use jing::parser::Parser;
+use jing::value::Value;
+use jing::vm::VM;
+
+/// Helper function to load a script into a fresh VM
+fn load_script(code: &str) -> JingResult<VM> {
+    let mut lexer = Lexer::new(code);
+    let tokens = lexer.tokenize()?;
+
+    let mut parser = Parser::new(tokens);
+    let statements = parser.parse()?;
+
+    let mut compiler = Compiler::new();
+    let chunk = compiler.compile(statements)?;
+
+    let mut vm = VM::new();
+    vm.interpret(chunk)?;
+    Ok(vm)
+}
+
+const PLUGIN: &str = r#"
+    let calls = 0;
+
+    fn on_event(name, count) {
+        calls = calls + 1;
+        return name + ":" + count;
+    }
+
+    fn factorial(n) {
+        if (n <= 1) {
+            return 1;
+        }
+        return n * factorial(n - 1);
+    }
+"#;
+
+#[test]
+fn test_call_script_function() {
+    let mut vm = load_script(PLUGIN).unwrap();
+
+    let result = vm
+        .call(
+            "on_event",
+            &[Value::String("click".to_string()), Value::Number(2.0)],
+        )
+        .unwrap();
+    assert_eq!(result, Value::String("click:2".to_string()));
+
+    assert_eq!(
+        vm.call("factorial", &[Value::Number(5.0)]).unwrap(),
+        Value::Number(120.0)
+    );
+}
+
+#[test]
+fn test_calls_keep_globals() {
+    let mut vm = load_script(PLUGIN).unwrap();
+
+    for _ in 0..3 {
+        vm.call("on_event", &[Value::String("tick".to_string()), Value::Nil])
+            .unwrap();
+    }
+    assert_eq!(vm.get_global("calls"), Some(Value::Number(3.0)));
+}
+
+#[test]
+fn test_call_errors_leave_vm_usable() {
+    let mut vm = load_script(PLUGIN).unwrap();
+
+    assert!(matches!(
+        vm.call("missing", &[]),
+        Err(JingError::RuntimeError { .. })
+    ));
+    assert!(vm.call("factorial", &[]).is_err());
+    assert!(vm.call("calls", &[]).is_err());
+
+    // A runtime error inside the function unwinds its frame
+    assert!(vm
+        .call("factorial", &[Value::String("x".to_string())])
+        .is_err());
+    assert_eq!(
+        vm.call("factorial", &[Value::Number(3.0)]).unwrap(),
+        Value::Number(6.0)
+    );
+}
+
+#[test]
+fn test_call_builtin_by_name() {
+    let mut vm = load_script("let x = 1;").unwrap();
+    assert_eq!(
+        vm.call("sqrt", &[Value::Number(9.0)]).unwrap(),
+        Value::Number(3.0)
+    );
+}
+
+#[test]
+fn test_script_args_and_exit() {
+    let mut vm = load_script("fn first() { return get(args(), 0); }").unwrap();
+    vm.set_args(vec!["input.txt".to_string(), "-v".to_string()]);
+    assert_eq!(
+        vm.call("first", &[]).unwrap(),
+        Value::String("input.txt".to_string())
+    );
+    assert_eq!(vm.args(), ["input.txt", "-v"]);
+
+    // exit() surfaces as an error carrying the status, leaving the VM usable
+    assert_eq!(
+        vm.call("exit", &[Value::Number(4.0)]),
+        Err(JingError::Exit { code: 4 })
+    );
+    assert!(vm.call("first", &[]).is_ok());
+}
+
+#[test]
+fn test_memory_limit_applies_to_each_call() {
+    let mut vm = load_script(r#"fn shout(s) { return upper(s); }"#).unwrap();
+    vm.set_memory_limit(Some(1000));
+    for _ in 0..200 {
+        let result = vm
+            .call("shout", &[Value::String("abcdefghij".to_string())])
+            .unwrap();
+        assert_eq!(result, Value::String("ABCDEFGHIJ".to_string()));
+    }
+    assert_eq!(vm.memory_used(), 10);
+
+    // A single call is still held to the limit
+    let big = "x".repeat(2000);
+    let result = vm.call("shout", &[Value::String(big)]);
+    assert!(matches!(result, Err(JingError::ResourceExhausted { .. })));
+}
@@ -199,3 +199,115 @@ fn test_complex_expressions() {
     let result = vm.get_global("result").unwrap();
     assert_eq!(result, Value::Number(18.0)); // (2+3)*4-2 = 5*4-2 = 18
 }
+
+#[test]
+fn test_default_and_rest_parameters() {
+    let input = r#"
+        fn greet(name, greeting = "Hello") {
+            return greeting + ", " + name;
+        }
+        fn sum(...nums) {
+            let total = 0;
+            let i = 0;
+            while (i < len(nums)) {
+                total = total + get(nums, i);
+                i = i + 1;
+            }
+            return total;
+        }
+        fn scaled(x, factor = x * 2, ...extra) {
+            return x * factor + len(extra);
+        }
+        let a = greet("Ada");
+        let b = greet("Ada", "Hi");
+        let c = sum();
+        let d = sum(1, 2, 3);
+        let e = scaled(3);
+        let f = scaled(3, 1, nil, nil);
+    "#;
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let statements = parser.parse().unwrap();
+    let mut compiler = Compiler::new();
+    let chunk = compiler.compile(statements).unwrap();
+    let mut vm = VM::new();
+    vm.interpret(chunk).unwrap();
+
+    let global = |name| vm.get_global(name).unwrap();
+    assert_eq!(global("a"), Value::String("Hello, Ada".to_string()));
+    assert_eq!(global("b"), Value::String("Hi, Ada".to_string()));
+    assert_eq!(global("c"), Value::Number(0.0));
+    assert_eq!(global("d"), Value::Number(6.0));
+    assert_eq!(global("e"), Value::Number(18.0));
+    assert_eq!(global("f"), Value::Number(5.0));
+
+    let err = vm.call("greet", &[]).unwrap_err();
+    assert!(err.to_string().contains("expects 1 to 2 arguments, got 0"));
+}
+
+#[test]
+fn test_named_arguments() {
+    let input = r#"
+        fn connect(host, port = 80, secure = false) {
+            return host + ":" + port + " " + secure;
+        }
+        let a = connect(host: "localhost", port: 8080);
+        let b = connect("example.com", secure: true);
+        let c = connect(port: 1, host: "h");
+    "#;
+    let tokens = Lexer::new(input).tokenize().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    let chunk = Compiler::new().compile(statements).unwrap();
+    let mut vm = VM::new();
+    vm.interpret(chunk).unwrap();
+
+    let global = |name| vm.get_global(name).unwrap();
+    assert_eq!(
+        global("a"),
+        Value::String("localhost:8080 false".to_string())
+    );
+    assert_eq!(
+        global("b"),
+        Value::String("example.com:80 true".to_string())
+    );
+    assert_eq!(global("c"), Value::String("h:1 false".to_string()));
+}
+
+#[test]
+fn test_named_argument_errors() {
+    let compile = |input: &str| {
+        let tokens = Lexer::new(input).tokenize().unwrap();
+        let statements = Parser::new(tokens).parse().unwrap();
+        Compiler::new().compile(statements)
+    };
+    let definition = "fn connect(host, port = 80) { return host; }";
+
+    // Statically known callee: checked at compile time
+    for (call, message) in [
+        (
+            "connect(host: 1, host: 2);",
+            "Duplicate named argument 'host'",
+        ),
+        ("connect(1, user: 2);", "has no parameter named 'user'"),
+        (
+            "connect(1, host: 2);",
+            "multiple values for argument 'host'",
+        ),
+    ] {
+        let err = compile(&format!("{} {}", definition, call)).unwrap_err();
+        assert!(err.to_string().contains(message), "{}", err);
+    }
+
+    // Through a variable the callee is only known at runtime
+    for (call, message) in [
+        ("f(1, user: 2);", "has no parameter named 'user'"),
+        ("f(port: 2);", "missing argument 'host'"),
+        ("print(value: 1);", "does not accept named arguments"),
+    ] {
+        let source = format!("{} let f = connect; {}", definition, call);
+        let chunk = compile(&source).unwrap();
+        let err = VM::new().interpret(chunk).unwrap_err();
+        assert!(err.to_string().contains(message), "{}", err);
+    }
+}
@@ -388,3 +388,29 @@ fn test_lexer_complex_expression() {
         }
     }));
 }
+
+#[test]
+fn test_lexer_keeps_comments_as_trivia() {
+    let input = "// heading\nlet x = 1; // one\n\n  //indented\nx;";
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().unwrap();
+
+    // Comments never reach the token stream
+    assert!(tokens
+        .iter()
+        .all(|token| !matches!(&token.token_type, TokenType::Identifier(name) if name == "one")));
+
+    let comments: Vec<_> = lexer
+        .comments()
+        .iter()
+        .map(|comment| (comment.text.as_str(), comment.line, comment.trailing))
+        .collect();
+    assert_eq!(
+        comments,
+        [
+            (" heading", 1, false),
+            (" one", 2, true),
+            ("indented", 4, false)
+        ]
+    );
+}
@@ -0,0 +1,312 @@
+use std::io::Cursor;
+
+use jing::lsp::analysis::{self, Document, Position};
+use jing::lsp::json::Json;
+use jing::lsp::{read_message, write_message, Server};
+
+fn message(text: &str) -> Json {
+    Json::parse(text).unwrap()
+}
+
+/// Frame `messages`, run a server over them and return its replies
+fn session(messages: &[Json]) -> (i32, Vec<Json>) {
+    let mut input = Vec::new();
+    for message in messages {
+        write_message(&mut input, message).unwrap();
+    }
+
+    let mut output = Vec::new();
+    let status = Server::new().run(Cursor::new(input), &mut output).unwrap();
+
+    let mut replies = Vec::new();
+    let mut reader = Cursor::new(output);
+    while let Some(body) = read_message(&mut reader).unwrap() {
+        replies.push(Json::parse(&body).unwrap());
+    }
+    (status, replies)
+}
+
+fn open(uri: &str, text: &str) -> Json {
+    Json::object([
+        ("jsonrpc", Json::from("2.0")),
+        ("method", Json::from("textDocument/didOpen")),
+        (
+            "params",
+            Json::object([(
+                "textDocument",
+                Json::object([
+                    ("uri", Json::from(uri)),
+                    ("languageId", Json::from("jing")),
+                    ("version", Json::from(1usize)),
+                    ("text", Json::from(text)),
+                ]),
+            )]),
+        ),
+    ])
+}
+
+fn request(id: usize, method: &str, uri: &str, line: usize, character: usize) -> Json {
+    Json::object([
+        ("jsonrpc", Json::from("2.0")),
+        ("id", Json::from(id)),
+        ("method", Json::from(method)),
+        (
+            "params",
+            Json::object([
+                ("textDocument", Json::object([("uri", Json::from(uri))])),
+                (
+                    "position",
+                    Json::object([
+                        ("line", Json::from(line)),
+                        ("character", Json::from(character)),
+                    ]),
+                ),
+            ]),
+        ),
+    ])
+}
+
+fn reply(replies: &[Json], id: usize) -> &Json {
+    replies
+        .iter()
+        .find(|reply| reply.get("id") == Some(&Json::from(id)))
+        .unwrap_or_else(|| panic!("no reply to request {}", id))
+}
+
+#[test]
+fn test_json_round_trip() {
+    let text = r#"{"a":[1,2.5,-3e2,true,false,null],"b":"q\"\\\né😀","c":{}}"#;
+    let value = Json::parse(text).unwrap();
+    assert_eq!(value.at(&["a"]).unwrap().as_array().unwrap().len(), 6);
+    assert_eq!(value.get("b").unwrap().as_str(), Some("q\"\\\né😀"));
+    assert_eq!(Json::parse(&value.to_string()).unwrap(), value);
+
+    assert!(Json::parse("{\"a\":}").is_err());
+    assert!(Json::parse("[1, 2").is_err());
+    assert!(Json::parse("1 2").is_err());
+
+    // Deep nesting is refused instead of overflowing the stack
+    let nested = format!("{}{}", "[".repeat(512), "]".repeat(512));
+    assert!(Json::parse(&nested).is_ok());
+    let hostile = "[".repeat(200_000);
+    assert_eq!(Json::parse(&hostile), Err("nesting too deep".to_string()));
+}
+
+#[test]
+fn test_positions_count_utf16_units() {
+    let document = Document::new("let s = \"😀\";\nprint(s);\n");
+    // The emoji is four bytes but two UTF-16 units
+    let end = document.text.find(';').unwrap();
+    assert_eq!(
+        document.position(end),
+        Position {
+            line: 0,
+            character: 12
+        }
+    );
+    assert_eq!(
+        document.offset(Position {
+            line: 0,
+            character: 12
+        }),
+        end
+    );
+    assert_eq!(
+        document.offset(Position {
+            line: 1,
+            character: 99
+        }),
+        document.text.len() - 1
+    );
+}
+
+#[test]
+fn test_language_features() {
+    let source = "\
+fn area(width, height = 1) {
+    let result = width * height;
+    fn helper() {}
+    return result;
+}
+let total = area(2, 3);
+print(len(\"x\") + total);
+";
+    let document = Document::new(source);
+    let registry = jing::Registry::with_defaults();
+    let offset_of = |needle: &str, nth: usize| source.match_indices(needle).nth(nth).unwrap().0;
+
+    // Parameters, globals and functions all resolve to their declarations
+    let width_use = offset_of("width", 1);
+    assert_eq!(
+        analysis::definition(&document, width_use).map(|span| span.start),
+        Some(offset_of("width", 0))
+    );
+    assert_eq!(
+        analysis::definition(&document, offset_of("total", 1)).map(|span| span.start),
+        Some(offset_of("total", 0))
+    );
+    assert_eq!(
+        analysis::definition(&document, offset_of("area", 1)).map(|span| span.start),
+        Some(offset_of("area", 0))
+    );
+    assert_eq!(analysis::definition(&document, offset_of("len", 0)), None);
+
+    let hover = analysis::hover(&document, offset_of("area", 1), &registry).unwrap();
+    assert!(hover.contains("fn area(width, height = 1)"));
+    let hover = analysis::hover(&document, offset_of("len", 0), &registry).unwrap();
+    assert!(hover.contains("len("), "{}", hover);
+
+    let labels: Vec<_> = analysis::completions(&document, &registry)
+        .into_iter()
+        .map(|item| item.label)
+        .collect();
+    for label in ["len", "print", "area", "total", "while"] {
+        assert!(labels.contains(&label.to_string()), "missing {}", label);
+    }
+
+    let symbols = analysis::document_symbols(&document);
+    assert_eq!(symbols.len(), 1);
+    assert_eq!(symbols[0].name, "area");
+    assert_eq!(symbols[0].children[0].name, "helper");
+
+    // A typo the lexer cannot read leaves the rest of the document usable
+    let source = "fn area(w) { return w $ 2; }\nlet total = area(2);\n";
+    let document = Document::new(source);
+    assert_eq!(analysis::document_symbols(&document)[0].name, "area");
+    assert_eq!(
+        analysis::definition(&document, source.rfind("area").unwrap()).map(|span| span.start),
+        Some(3)
+    );
+}
+
+#[test]
+fn test_diagnostics_cover_each_stage() {
+    let cases = [
+        ("let x = 1;\nlet y = 2 $ 3;\n", 1),
+        ("let x = 1;\n\nlet y = ;\n", 2),
+        ("fn f() { import \"std/math\"; }\n", 0),
+    ];
+    for (source, line) in cases {
+        let diagnostics = analysis::diagnostics(&Document::new(source));
+        assert_eq!(diagnostics.len(), 1, "{}", source);
+        assert_eq!(diagnostics[0].range.start.line, line, "{}", source);
+    }
+    assert!(analysis::diagnostics(&Document::new("print(1);")).is_empty());
+}
+
+#[test]
+fn test_scripted_session() {
+    let uri = "file:///tmp/main.jing";
+    let (status, replies) = session(&[
+        message(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#),
+        message(r#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#),
+        open(
+            uri,
+            "fn twice(n) {\n    return n * 2;\n}\nprint(twice(4));\nlet = 1;\n",
+        ),
+        request(2, "textDocument/definition", uri, 3, 7),
+        request(3, "textDocument/hover", uri, 3, 0),
+        request(4, "textDocument/documentSymbol", uri, 0, 0),
+        request(5, "textDocument/completion", uri, 3, 0),
+        request(6, "textDocument/formatting", uri, 0, 0),
+        message(r#"{"jsonrpc":"2.0","id":7,"method":"shutdown"}"#),
+        message(r#"{"jsonrpc":"2.0","method":"exit"}"#),
+    ]);
+    assert_eq!(status, 0);
+
+    let capabilities = reply(&replies, 1).at(&["result", "capabilities"]).unwrap();
+    assert_eq!(capabilities.get("hoverProvider"), Some(&Json::Bool(true)));
+
+    let diagnostics = replies
+        .iter()
+        .find(|reply| {
+            reply.get("method").and_then(Json::as_str) == Some("textDocument/publishDiagnostics")
+        })
+        .unwrap();
+    let diagnostic = &diagnostics
+        .at(&["params", "diagnostics"])
+        .unwrap()
+        .as_array()
+        .unwrap()[0];
+    assert_eq!(
+        diagnostic.at(&["range", "start", "line"]),
+        Some(&Json::from(4usize))
+    );
+
+    let definition = reply(&replies, 2).get("result").unwrap();
+    assert_eq!(definition.get("uri").and_then(Json::as_str), Some(uri));
+    assert_eq!(
+        definition.at(&["range", "start"]).unwrap().to_string(),
+        r#"{"line":0,"character":3}"#
+    );
+
+    let hover = reply(&replies, 3)
+        .at(&["result", "contents", "value"])
+        .and_then(Json::as_str)
+        .unwrap();
+    assert!(hover.contains("print"), "{}", hover);
+
+    let symbols = reply(&replies, 4)
+        .get("result")
+        .unwrap()
+        .as_array()
+        .unwrap();
+    assert_eq!(symbols[0].get("name").and_then(Json::as_str), Some("twice"));
+
+    let completions = reply(&replies, 5)
+        .get("result")
+        .unwrap()
+        .as_array()
+        .unwrap();
+    assert!(completions
+        .iter()
+        .any(|item| item.get("label").and_then(Json::as_str) == Some("sqrt")));
+
+    let error = reply(&replies, 6).get("error").unwrap();
+    assert_eq!(error.get("code"), Some(&Json::from(-32601i64)));
+}
+
+#[test]
+fn test_malformed_messages_are_answered() {
+    let mut input = b"Content-Length: 2\r\n\r\n\xff\xfe".to_vec();
+    write_message(
+        &mut input,
+        &message(r#"{"jsonrpc":"2.0","id":1,"method":"shutdown"}"#),
+    )
+    .unwrap();
+    // Too long to read, then a body cut short by the end of input
+    input.extend_from_slice(b"Content-Length: 999999999999\r\n\r\n{}");
+    let mut short = b"Content-Length: 50\r\n\r\n{}".to_vec();
+
+    let mut output = Vec::new();
+    let status = Server::new().run(Cursor::new(input), &mut output).unwrap();
+    assert_eq!(status, 0);
+    let mut reader = Cursor::new(output);
+    let mut codes = Vec::new();
+    while let Some(body) = read_message(&mut reader).unwrap() {
+        let reply = Json::parse(&body).unwrap();
+        codes.push(reply.at(&["error", "code"]).cloned());
+    }
+    assert_eq!(
+        codes,
+        [
+            Some(Json::from(-32700i64)),
+            None,
+            Some(Json::from(-32700i64))
+        ]
+    );
+
+    let mut output = Vec::new();
+    Server::new()
+        .run(Cursor::new(&mut short), &mut output)
+        .unwrap();
+    let body = read_message(&mut Cursor::new(output)).unwrap().unwrap();
+    assert!(body.contains("-32700"), "{}", body);
+}
+
+#[test]
+fn test_exit_without_shutdown_fails() {
+    let (status, replies) = session(&[message(r#"{"jsonrpc":"2.0","method":"exit"}"#)]);
+    assert_eq!(status, 1);
+    assert!(replies.is_empty());
+}
@@ -0,0 +1,350 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+use jing::compiler::Compiler;
+use jing::error::{JingError, JingResult};
+use jing::lexer::Lexer;
+use jing::parser::Parser;
+use jing::sandbox::Capabilities;
+use jing::value::Value;
+use jing::vm::VM;
+
+/// Write `source` to `name` inside `dir`, creating parent directories
+fn write_module(dir: &Path, name: &str, source: &str) {
+    let path = dir.join(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, source).unwrap();
+}
+
+/// Run `main.jing` from `dir` the way the CLI does
+fn run_main(dir: &Path, capabilities: Capabilities) -> JingResult<VM> {
+    let path = dir.join("main.jing");
+    let source = fs::read_to_string(&path).unwrap();
+
+    let tokens = Lexer::new(&source).tokenize()?;
+    let statements = Parser::new(tokens).parse()?;
+    let chunk = Compiler::new().compile(statements)?;
+
+    let mut vm = VM::new();
+    vm.set_capabilities(capabilities);
+    vm.set_script_path(path);
+    vm.interpret(chunk)?;
+    Ok(vm)
+}
+
+#[test]
+fn test_import_exports_into_namespace() {
+    let dir = TempDir::new().unwrap();
+    write_module(
+        dir.path(),
+        "utils/strings.jing",
+        r#"
+        let fill = "*";
+        export let width = 5;
+        export fn pad(text, right = false) {
+            if (right) { return fill + text; }
+            return text + fill;
+        }
+        "#,
+    );
+    write_module(
+        dir.path(),
+        "main.jing",
+        r#"
+        import "utils/strings.jing" as strs;
+        let padded = strs.pad("a");
+        let right = strs.pad("b", right: true);
+        let width = strs.width;
+        let fill = "main";
+        "#,
+    );
+
+    let vm = run_main(dir.path(), Capabilities::all()).unwrap();
+    assert_eq!(vm.get_global("padded"), Some(Value::String("a*".into())));
+    assert_eq!(vm.get_global("right"), Some(Value::String("*b".into())));
+    assert_eq!(vm.get_global("width"), Some(Value::Number(5.0)));
+    // The module's own globals do not leak into the importer
+    assert_eq!(vm.get_global("fill"), Some(Value::String("main".into())));
+    assert!(vm.get_global("pad").is_none());
+}
+
+#[test]
+fn test_unexported_names_are_private() {
+    let dir = TempDir::new().unwrap();
+    write_module(
+        dir.path(),
+        "lib.jing",
+        "let secret = 1; export let open = 2;",
+    );
+    write_module(
+        dir.path(),
+        "main.jing",
+        r#"import "lib"; let x = lib.secret;"#,
+    );
+
+    let result = run_main(dir.path(), Capabilities::all());
+    assert!(
+        matches!(result, Err(JingError::RuntimeError { message }) if message.contains("secret"))
+    );
+}
+
+#[test]
+fn test_modules_run_once_and_share_state() {
+    let dir = TempDir::new().unwrap();
+    write_module(
+        dir.path(),
+        "counter.jing",
+        "let count = 0; export fn next() { count = count + 1; return count; }",
+    );
+    write_module(
+        dir.path(),
+        "other.jing",
+        r#"import "counter.jing"; export fn bump() { return counter.next(); }"#,
+    );
+    write_module(
+        dir.path(),
+        "main.jing",
+        r#"
+        import "counter.jing" as a;
+        import "other.jing";
+        import "counter.jing" as b;
+        let first = a.next();
+        let second = other.bump();
+        let third = b.next();
+        "#,
+    );
+
+    let vm = run_main(dir.path(), Capabilities::all()).unwrap();
+    assert_eq!(vm.get_global("first"), Some(Value::Number(1.0)));
+    assert_eq!(vm.get_global("second"), Some(Value::Number(2.0)));
+    assert_eq!(vm.get_global("third"), Some(Value::Number(3.0)));
+}
+
+#[test]
+fn test_namespace_is_a_snapshot() {
+    let dir = TempDir::new().unwrap();
+    write_module(
+        dir.path(),
+        "counter.jing",
+        "export let count = 0;\n\
+         export fn next() { count = count + 1; return count; }\n\
+         export fn current() { return count; }",
+    );
+    write_module(
+        dir.path(),
+        "main.jing",
+        r#"
+        import "counter.jing";
+        counter.next();
+        let copied = counter.count;
+        let live = counter.current();
+        "#,
+    );
+
+    let vm = run_main(dir.path(), Capabilities::all()).unwrap();
+    assert_eq!(vm.get_global("copied"), Some(Value::Number(0.0)));
+    assert_eq!(vm.get_global("live"), Some(Value::Number(1.0)));
+}
+
+#[test]
+fn test_imports_resolve_relative_to_importing_file() {
+    let dir = TempDir::new().unwrap();
+    write_module(
+        dir.path(),
+        "lib/helper.jing",
+        "export let name = \"helper\";",
+    );
+    write_module(
+        dir.path(),
+        "lib/util.jing",
+        r#"import "helper.jing"; export fn name() { return helper.name; }"#,
+    );
+    write_module(
+        dir.path(),
+        "main.jing",
+        r#"import "lib/util.jing"; let name = util.name();"#,
+    );
+
+    let vm = run_main(dir.path(), Capabilities::all()).unwrap();
+    assert_eq!(vm.get_global("name"), Some(Value::String("helper".into())));
+}
+
+#[test]
+fn test_circular_and_missing_imports() {
+    let dir = TempDir::new().unwrap();
+    write_module(
+        dir.path(),
+        "a.jing",
+        r#"import "b.jing"; export let x = 1;"#,
+    );
+    write_module(
+        dir.path(),
+        "b.jing",
+        r#"import "a.jing"; export let y = 2;"#,
+    );
+    write_module(dir.path(), "main.jing", r#"import "a.jing";"#);
+
+    let result = run_main(dir.path(), Capabilities::all());
+    assert!(
+        matches!(&result, Err(JingError::RuntimeError { message }) if message.contains("Circular import")),
+        "{:?}",
+        result.err()
+    );
+
+    write_module(dir.path(), "main.jing", r#"import "nope.jing";"#);
+    let result = run_main(dir.path(), Capabilities::all());
+    assert!(
+        matches!(result, Err(JingError::RuntimeError { message }) if message.contains("Cannot find module"))
+    );
+
+    write_module(dir.path(), "broken.jing", "let = ;");
+    write_module(dir.path(), "main.jing", r#"import "broken.jing";"#);
+    let result = run_main(dir.path(), Capabilities::all());
+    assert!(matches!(result, Err(JingError::CompileError { .. })));
+}
+
+#[test]
+fn test_imports_follow_sandbox_and_top_level_rules() {
+    let dir = TempDir::new().unwrap();
+    write_module(dir.path(), "lib.jing", "export let x = 1;");
+    write_module(dir.path(), "main.jing", r#"import "lib.jing";"#);
+
+    let result = run_main(dir.path(), Capabilities::none());
+    assert!(matches!(result, Err(JingError::PermissionDenied { .. })));
+
+    // A denied import fails the same way whether or not the file exists
+    write_module(dir.path(), "main.jing", r#"import "missing.jing";"#);
+    let result = run_main(dir.path(), Capabilities::none());
+    assert!(matches!(result, Err(JingError::PermissionDenied { .. })));
+
+    let allowed = dir.path().join("app");
+    write_module(&allowed, "main.jing", r#"import "../lib.jing";"#);
+    let result = run_main(&allowed, Capabilities::directories([&allowed]));
+    assert!(matches!(result, Err(JingError::PermissionDenied { .. })));
+    write_module(&allowed, "main.jing", r#"import "../missing.jing";"#);
+    let result = run_main(&allowed, Capabilities::directories([&allowed]));
+    assert!(matches!(result, Err(JingError::PermissionDenied { .. })));
+
+    write_module(
+        dir.path(),
+        "main.jing",
+        r#"fn load() { import "lib.jing"; }"#,
+    );
+    let result = run_main(dir.path(), Capabilities::all());
+    assert!(matches!(result, Err(JingError::CompileError { .. })));
+}
+
+#[test]
+fn test_jing_path_is_searched() {
+    let dir = TempDir::new().unwrap();
+    write_module(
+        dir.path(),
+        "shared/greet.jing",
+        r#"export fn hi() { return "hi"; }"#,
+    );
+    write_module(
+        dir.path(),
+        "app/main.jing",
+        r#"import "greet"; print(greet.hi());"#,
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_jing"))
+        .arg(dir.path().join("app/main.jing"))
+        .env("JING_PATH", dir.path().join("shared"))
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hi\n");
+}
+
+#[test]
+fn test_jing_path_is_read_through_the_vm_environment() {
+    let dir = TempDir::new().unwrap();
+    write_module(
+        dir.path(),
+        "shared/greet.jing",
+        r#"export fn hi() { return "hi"; }"#,
+    );
+    let shared = dir.path().join("shared").to_string_lossy().into_owned();
+    write_module(
+        dir.path(),
+        "main.jing",
+        &format!(
+            r#"set_env("JING_PATH", {:?}); import "greet"; let out = greet.hi();"#,
+            shared
+        ),
+    );
+    let vm = run_main(dir.path(), Capabilities::all()).unwrap();
+    assert_eq!(vm.get_global("out"), Some(Value::String("hi".into())));
+
+    write_module(dir.path(), "main.jing", r#"import "greet";"#);
+    let source = fs::read_to_string(dir.path().join("main.jing")).unwrap();
+    let tokens = Lexer::new(&source).tokenize().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    let chunk = Compiler::new().compile(statements).unwrap();
+    let mut vm = VM::new();
+    vm.set_capabilities(Capabilities::all().with_env(false));
+    vm.set_script_path(dir.path().join("main.jing"));
+    vm.set_env_var("JING_PATH", &shared);
+    let err = vm.interpret(chunk).unwrap_err();
+    assert!(err.to_string().contains("Cannot find module"), "{}", err);
+}
+
+/// Run `code` without a script path, as `jing -e` does
+fn run_code(code: &str, capabilities: Capabilities) -> JingResult<VM> {
+    let tokens = Lexer::new(code).tokenize()?;
+    let statements = Parser::new(tokens).parse()?;
+    let chunk = Compiler::new().compile(statements)?;
+
+    let mut vm = VM::new();
+    vm.set_capabilities(capabilities);
+    vm.interpret(chunk)?;
+    Ok(vm)
+}
+
+#[test]
+fn test_bundled_modules_compile() {
+    for (name, source) in jing::module::STD_MODULES {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        let statements = Parser::new(tokens).parse().unwrap();
+        let chunk = Compiler::new().compile(statements).unwrap();
+        assert!(!chunk.exports.is_empty(), "{} exports nothing", name);
+    }
+}
+
+#[test]
+fn test_std_modules_need_no_filesystem() {
+    let vm = run_code(
+        r#"
+        import "std/strings";
+        import "std/math.jing" as m;
+        import "std/assert";
+        let padded = strings.pad_left("7", 3, "0");
+        let joined = strings.join(range(3), "-");
+        let power = m.pow(2, 10);
+        let divisor = m.gcd(12, 18);
+        let average = m.mean(range(5));
+        assert.equal(m.lcm(4, 6), 12);
+        assert.ok(m.is_odd(3));
+        "#,
+        Capabilities::none(),
+    )
+    .unwrap();
+
+    assert_eq!(vm.get_global("padded"), Some(Value::String("007".into())));
+    assert_eq!(vm.get_global("joined"), Some(Value::String("0-1-2".into())));
+    assert_eq!(vm.get_global("power"), Some(Value::Number(1024.0)));
+    assert_eq!(vm.get_global("divisor"), Some(Value::Number(6.0)));
+    assert_eq!(vm.get_global("average"), Some(Value::Number(2.0)));
+
+    let result = run_code(
+        r#"import "std/assert"; assert.equal(1, 2);"#,
+        Capabilities::none(),
+    );
+    assert!(matches!(result, Err(JingError::AssertionFailed { .. })));
+
+    let result = run_code(r#"import "std/nope";"#, Capabilities::none());
+    assert!(result.is_err());
+}
@@ -0,0 +1,73 @@
+use jing::compiler::Compiler;
+use jing::error::{JingError, JingResult};
+use jing::features::Arity;
+use jing::lexer::Lexer;
+use jing::parser::Parser;
+use jing::value::Value;
+use jing::vm::VM;
+
+/// Helper function to run Jing code on an existing VM
+fn run_on(vm: &mut VM, code: &str) -> JingResult<Value> {
+    let mut lexer = Lexer::new(code);
+    let tokens = lexer.tokenize()?;
+
+    let mut parser = Parser::new(tokens);
+    let statements = parser.parse()?;
+
+    let mut compiler = Compiler::new();
+    let chunk = compiler.compile(statements)?;
+
+    vm.interpret(chunk)?;
+    vm.get_result()
+}
+
+#[test]
+fn test_register_closure() {
+    let mut vm = VM::new();
+    vm.register_fn("add", |a: f64, b: f64| a + b);
+    vm.register_fn("greet", |name: String| format!("Hello, {}!", name));
+    vm.register_fn("answer", || 42.0);
+
+    assert_eq!(run_on(&mut vm, "add(2, 3);").unwrap(), Value::Number(5.0));
+    assert_eq!(
+        run_on(&mut vm, r#"greet("Jing");"#).unwrap(),
+        Value::String("Hello, Jing!".to_string())
+    );
+    assert_eq!(run_on(&mut vm, "answer();").unwrap(), Value::Number(42.0));
+    assert_eq!(vm.registry().get("add").unwrap().arity(), Arity::exact(2));
+}
+
+#[test]
+fn test_closure_argument_type_errors() {
+    let mut vm = VM::new();
+    vm.register_fn("add", |a: f64, b: f64| a + b);
+
+    match run_on(&mut vm, r#"add(1, "two");"#).unwrap_err() {
+        JingError::TypeError { message } => {
+            assert_eq!(message, "add() argument 2 expected number, got string");
+        }
+        other => panic!("Expected TypeError, got {:?}", other),
+    }
+
+    assert!(run_on(&mut vm, "add(1);").is_err());
+}
+
+#[test]
+fn test_fallible_closure() {
+    let mut vm = VM::new();
+    vm.register_fn("checked_div", |a: f64, b: f64| -> JingResult<f64> {
+        if b == 0.0 {
+            Err(JingError::runtime_error("division by zero"))
+        } else {
+            Ok(a / b)
+        }
+    });
+    vm.register_fn("log", |_message: String| {});
+
+    assert_eq!(
+        run_on(&mut vm, "checked_div(9, 3);").unwrap(),
+        Value::Number(3.0)
+    );
+    assert!(run_on(&mut vm, "checked_div(1, 0);").is_err());
+    assert_eq!(run_on(&mut vm, r#"log("hi");"#).unwrap(), Value::Nil);
+}
@@ -1,6 +1,6 @@
 use jing::lexer::Lexer;
 use jing::parser::{
-    BinaryOperator, Expr, LiteralValue, LogicalOperator, Parser, Stmt, UnaryOperator,
+    BinaryOperator, Expr, LiteralValue, LogicalOperator, Param, Parser, Stmt, UnaryOperator,
 };
 
 #[test]
@@ -359,8 +359,8 @@ fn test_parse_function_statement() {
         Stmt::Function(fn_stmt) => {
             assert_eq!(fn_stmt.name, "add");
             assert_eq!(fn_stmt.params.len(), 2);
-            assert_eq!(fn_stmt.params[0], "a");
-            assert_eq!(fn_stmt.params[1], "b");
+            assert_eq!(fn_stmt.params[0], Param::required("a"));
+            assert_eq!(fn_stmt.params[1], Param::required("b"));
 
             match &*fn_stmt.body {
                 Stmt::Block(_) => (),
@@ -600,3 +600,102 @@ fn test_parse_parenthesized_expressions() {
         _ => panic!("Expected expression statement"),
     }
 }
+
+#[test]
+fn test_parse_default_and_rest_parameters() {
+    let input = r#"fn greet(name, greeting = "Hello", ...rest) { return name; }"#;
+    let mut lexer = Lexer::new(input);
+    let tokens = lexer.tokenize().unwrap();
+    let mut parser = Parser::new(tokens);
+    let statements = parser.parse().unwrap();
+
+    match &statements[0] {
+        Stmt::Function(fn_stmt) => {
+            assert_eq!(fn_stmt.params[0], Param::required("name"));
+            assert_eq!(fn_stmt.params[1].name, "greeting");
+            assert!(fn_stmt.params[1].default.is_some());
+            assert!(fn_stmt.params[2].rest);
+            assert!(fn_stmt.params[2].default.is_none());
+        }
+        _ => panic!("Expected function statement"),
+    }
+
+    for input in [
+        "fn f(...a, b) {}",
+        "fn f(a = 1, b) {}",
+        "fn f(...a = 1) {}",
+        "fn f(a, a) {}",
+    ] {
+        let tokens = Lexer::new(input).tokenize().unwrap();
+        assert!(
+            Parser::new(tokens).parse().is_err(),
+            "{} should not parse",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_parse_named_arguments() {
+    let tokens = Lexer::new(r#"connect("db", port: 8080);"#)
+        .tokenize()
+        .unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+
+    match &statements[0] {
+        Stmt::Expression(expr_stmt) => match &expr_stmt.expr {
+            Expr::Call(call) => {
+                assert_eq!(call.args.len(), 1);
+                assert_eq!(call.named.len(), 1);
+                assert_eq!(call.named[0].name, "port");
+            }
+            _ => panic!("Expected call expression"),
+        },
+        _ => panic!("Expected expression statement"),
+    }
+
+    let tokens = Lexer::new("f(a: 1, 2);").tokenize().unwrap();
+    assert!(Parser::new(tokens).parse().is_err());
+}
+
+#[test]
+fn test_parse_import_export_and_field_access() {
+    let tokens = Lexer::new(
+        r#"import "utils/strings.jing" as strs; import "lib/math"; export fn f() {} strs.pad(1);"#,
+    )
+    .tokenize()
+    .unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+
+    match &statements[0] {
+        Stmt::Import(import) => {
+            assert_eq!(import.path, "utils/strings.jing");
+            assert_eq!(import.alias, "strs");
+        }
+        _ => panic!("Expected import statement"),
+    }
+    match &statements[1] {
+        Stmt::Import(import) => assert_eq!(import.alias, "math"),
+        _ => panic!("Expected import statement"),
+    }
+    match &statements[2] {
+        Stmt::Export(export) => assert!(matches!(*export.declaration, Stmt::Function(_))),
+        _ => panic!("Expected export statement"),
+    }
+    match &statements[3] {
+        Stmt::Expression(expr_stmt) => match &expr_stmt.expr {
+            Expr::Call(call) => match call.callee.as_ref() {
+                Expr::Get(get) => assert_eq!(get.name, "pad"),
+                _ => panic!("Expected field access"),
+            },
+            _ => panic!("Expected call expression"),
+        },
+        _ => panic!("Expected expression statement"),
+    }
+
+    // Names that are not identifiers need an explicit alias
+    let tokens = Lexer::new(r#"import "my-lib.jing";"#).tokenize().unwrap();
+    assert!(Parser::new(tokens).parse().is_err());
+    let tokens = Lexer::new("export print(1);").tokenize().unwrap();
+    assert!(Parser::new(tokens).parse().is_err());
+}
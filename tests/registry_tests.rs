@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use jing::builtins::math::SqrtFunction;
+use jing::compiler::Compiler;
+use jing::error::JingResult;
+use jing::lexer::Lexer;
+use jing::parser::Parser;
+use jing::registry::Registry;
+use jing::value::Value;
+use jing::vm::VM;
+
+/// Helper function to run Jing code on an existing VM
+fn run_on(vm: &mut VM, code: &str) -> JingResult<Value> {
+    let mut lexer = Lexer::new(code);
+    let tokens = lexer.tokenize()?;
+
+    let mut parser = Parser::new(tokens);
+    let statements = parser.parse()?;
+
+    let mut compiler = Compiler::new();
+    let chunk = compiler.compile(statements)?;
+
+    vm.interpret(chunk)?;
+    vm.get_result()
+}
+
+#[test]
+fn test_default_registry_has_standard_builtins() {
+    let registry = Registry::with_defaults();
+    for name in ["print", "type", "sqrt", "len", "read_file"] {
+        assert!(registry.contains(name), "Missing builtin: {}", name);
+    }
+
+    let names = registry.names();
+    let mut sorted = names.clone();
+    sorted.sort();
+    assert_eq!(names, sorted);
+
+    assert_eq!(
+        registry.call("abs", vec![Value::Number(-3.0)]).unwrap(),
+        Value::Number(3.0)
+    );
+    assert!(registry.call("missing", vec![]).is_err());
+}
+
+#[test]
+fn test_vms_have_independent_registries() {
+    let mut full = VM::new();
+    let mut restricted = VM::new();
+    restricted.registry_mut().unregister("sqrt");
+
+    assert_eq!(run_on(&mut full, "sqrt(16);").unwrap(), Value::Number(4.0));
+    assert!(run_on(&mut restricted, "sqrt(16);").is_err());
+
+    // The other VM is unaffected by the removal
+    assert_eq!(run_on(&mut full, "sqrt(9);").unwrap(), Value::Number(3.0));
+}
+
+#[test]
+fn test_custom_registry() {
+    let mut registry = Registry::new();
+    registry.register(Arc::new(SqrtFunction));
+
+    let mut vm = VM::with_registry(registry);
+    assert_eq!(run_on(&mut vm, "sqrt(25);").unwrap(), Value::Number(5.0));
+    assert!(run_on(&mut vm, r#"upper("a");"#).is_err());
+    assert_eq!(vm.registry().names(), vec!["sqrt".to_string()]);
+}
+
+#[test]
+fn test_variadic_and_optional_arguments() {
+    let mut vm = VM::new();
+    assert_eq!(
+        run_on(&mut vm, "max(1, 5, 3);").unwrap(),
+        Value::Number(5.0)
+    );
+    assert_eq!(run_on(&mut vm, "min(4);").unwrap(), Value::Number(4.0));
+    assert_eq!(
+        run_on(&mut vm, "min(4, -2, 7, 0);").unwrap(),
+        Value::Number(-2.0)
+    );
+    assert_eq!(run_on(&mut vm, "round(2.5);").unwrap(), Value::Number(3.0));
+    assert_eq!(
+        run_on(&mut vm, "round(2.71828, 2);").unwrap(),
+        Value::Number(2.72)
+    );
+    assert_eq!(
+        run_on(&mut vm, "print(1, \"a\", true);").unwrap(),
+        Value::Nil
+    );
+    assert_eq!(run_on(&mut vm, "print();").unwrap(), Value::Nil);
+
+    let err = run_on(&mut vm, "max();").unwrap_err();
+    assert!(err.to_string().contains("expects at least 1 arguments"));
+    let err = run_on(&mut vm, "round(1, 2, 3);").unwrap_err();
+    assert!(err.to_string().contains("expects 1 to 2 arguments"));
+}
+
+#[test]
+fn test_registry_help_reports_arity() {
+    let registry = Registry::with_defaults();
+    assert!(registry
+        .help("round")
+        .unwrap()
+        .ends_with("(takes 1 to 2 arguments)"));
+    assert!(registry
+        .help("max")
+        .unwrap()
+        .ends_with("(takes at least 1 argument)"));
+    assert!(registry
+        .help("sqrt")
+        .unwrap()
+        .ends_with("(takes 1 argument)"));
+    assert!(registry
+        .help("print")
+        .unwrap()
+        .ends_with("(takes any number of arguments)"));
+    assert!(registry.help("missing").is_none());
+    assert!(registry.call("sqrt", vec![]).is_err());
+}
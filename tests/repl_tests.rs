@@ -0,0 +1,301 @@
+use jing::repl::editor::{
+    read_key, Action, Completer, History, Key, LineBuffer, NoCompletion, ReadResult,
+};
+use jing::repl::{needs_continuation, REPL};
+use jing::value::Value;
+
+#[test]
+fn test_functions_persist_across_lines() {
+    let mut repl = REPL::new();
+    repl.eval("fn add(a, b) { return a + b; }").unwrap();
+    repl.eval("let x = 40;").unwrap();
+    repl.eval("let y = add(x, 2);").unwrap();
+    assert_eq!(repl.vm().get_global("y").unwrap(), Value::Number(42.0));
+
+    // Functions can call functions from earlier lines
+    repl.eval("fn twice(n) { return add(n, n); }").unwrap();
+    repl.eval("let z = twice(5);").unwrap();
+    assert_eq!(repl.vm().get_global("z").unwrap(), Value::Number(10.0));
+}
+
+#[test]
+fn test_errors_do_not_break_the_session() {
+    let mut repl = REPL::new();
+    repl.eval("fn half(n) { return n / 2; }").unwrap();
+
+    assert!(repl.eval("let broken = ;").is_err());
+    assert!(repl.eval("half(1, 2);").is_err());
+    assert!(repl
+        .eval("fn fail() { return undefined_name; } fail();")
+        .is_err());
+    // A compile error undoes the whole line, including redefinitions
+    assert!(repl.eval("fn half(m) { return 0; } half(x: 1);").is_err());
+
+    repl.eval("let h = half(n: 8);").unwrap();
+    assert_eq!(repl.vm().get_global("h").unwrap(), Value::Number(4.0));
+}
+
+#[test]
+fn test_redefining_a_function() {
+    let mut repl = REPL::new();
+    repl.eval("fn f() { return 1; }").unwrap();
+    repl.eval("fn f() { return 2; }").unwrap();
+    repl.eval("let r = f();").unwrap();
+    assert_eq!(repl.vm().get_global("r").unwrap(), Value::Number(2.0));
+}
+
+#[test]
+fn test_eval_returns_last_expression() {
+    let mut repl = REPL::new();
+    assert_eq!(repl.eval("1 + 2;").unwrap(), Value::Number(3.0));
+    assert_eq!(repl.eval("_ * 2;").unwrap(), Value::Number(6.0));
+    assert_eq!(
+        repl.eval("\"a\" + \"b\";").unwrap(),
+        Value::String("ab".to_string())
+    );
+    assert_eq!(repl.eval("_;").unwrap().repr(), "\"ab\"");
+
+    // Statements and nil results leave `_` unchanged
+    assert_eq!(repl.eval("let x = 5;").unwrap(), Value::Nil);
+    assert_eq!(repl.eval("nil;").unwrap(), Value::Nil);
+    assert_eq!(
+        repl.vm().get_global("_").unwrap(),
+        Value::String("ab".to_string())
+    );
+}
+
+#[test]
+fn test_final_semicolon_is_optional() {
+    let mut repl = REPL::new();
+    assert_eq!(repl.eval("1 + 2").unwrap(), Value::Number(3.0));
+    assert_eq!(repl.eval("let x = 4").unwrap(), Value::Nil);
+    assert_eq!(repl.eval("x * 2 // comment").unwrap(), Value::Number(8.0));
+    assert_eq!(repl.eval("fn f() { return 1; }").unwrap(), Value::Nil);
+}
+
+#[test]
+fn test_needs_continuation() {
+    assert!(needs_continuation("fn add(a, b) {"));
+    assert!(needs_continuation("fn add(a, b) {\n return a +"));
+    assert!(needs_continuation("let s = max(1,"));
+    assert!(needs_continuation("let s = \"unterminated"));
+    assert!(!needs_continuation("fn add(a, b) { return a + b; }"));
+    assert!(!needs_continuation("1 + 2"));
+    // Extra closing brackets are an error to report, not a reason to wait
+    assert!(!needs_continuation("1 + 2)"));
+    assert!(!needs_continuation("let x = @;"));
+    // An error before the open bracket's end is reported right away
+    assert!(!needs_continuation("let y = sq(;"));
+    assert!(!needs_continuation("fn f() { let = 1;"));
+}
+
+#[test]
+fn test_multi_line_input() {
+    let mut repl = REPL::new();
+    repl.eval("fn add(a, b) {\n    return a +\n        b;\n}\n")
+        .unwrap();
+    assert_eq!(
+        repl.eval("add(\n  1,\n  2\n)\n").unwrap(),
+        Value::Number(3.0)
+    );
+}
+
+#[test]
+fn test_read_key_decodes_sequences() {
+    let mut input: &[u8] = b"a\x1b[A\x1b[D\x1b[3~\x7f\r\x03\xc3\xa9";
+    let expected = [
+        Key::Char('a'),
+        Key::Up,
+        Key::Left,
+        Key::Delete,
+        Key::Backspace,
+        Key::Enter,
+        Key::Interrupt,
+        Key::Char('\u{e9}'),
+        Key::Eof,
+    ];
+    for key in expected {
+        assert_eq!(read_key(&mut input).unwrap(), key);
+    }
+}
+
+#[test]
+fn test_read_key_ends_modified_sequences_at_final_byte() {
+    // Ctrl+Right has no `~`, so it must not swallow the keys after it
+    let mut input: &[u8] = b"\x1b[1;5Cx";
+    assert_eq!(read_key(&mut input).unwrap(), Key::Ignored);
+    assert_eq!(read_key(&mut input).unwrap(), Key::Char('x'));
+}
+
+#[test]
+fn test_line_buffer_editing() {
+    let history = History::new();
+    let mut buffer = LineBuffer::new();
+    for key in [
+        Key::Char('1'),
+        Key::Char('3'),
+        Key::Left,
+        Key::Char('2'),
+        Key::End,
+        Key::Backspace,
+        Key::Home,
+        Key::Delete,
+    ] {
+        assert_eq!(buffer.apply(key, &history), Action::Continue);
+    }
+    assert_eq!(buffer.text(), "2");
+    assert_eq!(
+        buffer.apply(Key::Enter, &history),
+        Action::Done(ReadResult::Line("2".to_string()))
+    );
+
+    let mut empty = LineBuffer::new();
+    assert_eq!(
+        empty.apply(Key::Eof, &history),
+        Action::Done(ReadResult::Eof)
+    );
+}
+
+#[test]
+fn test_history_navigation_and_persistence() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("history");
+
+    let mut history = History::load(&path);
+    history.add("let x = 1;");
+    history.add("let x = 1;");
+    history.add("   ");
+    history.add("x + 1");
+    history.save().unwrap();
+
+    let history = History::load(&path);
+    assert_eq!(history.entries(), ["let x = 1;", "x + 1"]);
+
+    let mut buffer = LineBuffer::new();
+    buffer.apply(Key::Char('d'), &history);
+    buffer.apply(Key::Up, &history);
+    assert_eq!(buffer.text(), "x + 1");
+    buffer.apply(Key::Up, &history);
+    buffer.apply(Key::Up, &history);
+    assert_eq!(buffer.text(), "let x = 1;");
+    buffer.apply(Key::Down, &history);
+    assert_eq!(buffer.text(), "x + 1");
+    // Going past the newest entry restores the draft
+    buffer.apply(Key::Down, &history);
+    assert_eq!(buffer.text(), "d");
+}
+
+#[test]
+fn test_help_command() {
+    let mut repl = REPL::new();
+    let all = repl.command(":help").unwrap();
+    assert!(all.contains(":load <file>"));
+    assert!(all.contains("sqrt(number) - Return the square root of a number"));
+
+    let one = repl.command(":help round").unwrap();
+    assert!(one.starts_with("round(number, digits?)"));
+    assert!(one.ends_with("(takes 1 to 2 arguments)"));
+
+    assert!(repl.command(":help missing").is_err());
+    assert!(repl.command(":nope").is_err());
+}
+
+#[test]
+fn test_vars_type_time_and_reset_commands() {
+    let mut repl = REPL::new();
+    assert_eq!(repl.command(":vars").unwrap(), "No variables defined");
+
+    repl.eval("let name = \"jing\"; fn f() { return 1; }")
+        .unwrap();
+    let vars = repl.command(":vars").unwrap();
+    assert!(vars.contains("name = \"jing\""));
+    assert!(vars.contains("f = <fn f(0 args)>"));
+
+    assert_eq!(repl.command(":type name").unwrap(), "string");
+    assert_eq!(repl.command(":type f()").unwrap(), "number");
+    assert!(repl.command(":type").is_err());
+
+    let timed = repl.command(":time f() + 1").unwrap();
+    assert!(timed.starts_with("2\nTook "));
+
+    repl.command(":reset").unwrap();
+    assert_eq!(repl.command(":vars").unwrap(), "No variables defined");
+    assert!(repl.eval("f()").is_err());
+}
+
+#[test]
+fn test_load_command() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("lib.jing");
+    std::fs::write(
+        &path,
+        "fn square(x) { return x * x; }\nlet loaded = true;\n",
+    )
+    .unwrap();
+
+    let mut repl = REPL::new();
+    let output = repl.command(&format!(":load {}", path.display())).unwrap();
+    assert!(output.starts_with("Loaded "));
+    assert_eq!(repl.eval("square(4)").unwrap(), Value::Number(16.0));
+    assert_eq!(repl.eval("loaded").unwrap(), Value::Bool(true));
+
+    assert!(repl.command(":load /no/such/file.jing").is_err());
+}
+
+#[test]
+fn test_completion_candidates() {
+    let mut repl = REPL::new();
+    repl.eval("let file_count = 3; fn fib(n) { return n; }")
+        .unwrap();
+
+    let completion = repl.complete("print(fi", 8);
+    assert_eq!(completion.start, 6);
+    assert_eq!(
+        completion.candidates,
+        ["fib", "file_count", "file_exists", "filter"]
+    );
+    assert_eq!(completion.hint, None);
+
+    // Keywords come from the lexer's table
+    assert_eq!(repl.complete("whi", 3).candidates, ["while"]);
+
+    // A single builtin shows its arity and help
+    let completion = repl.complete("file_e", 6);
+    assert_eq!(completion.candidates, ["file_exists"]);
+    assert!(completion.hint.unwrap().contains("(takes 1 argument)"));
+
+    assert!(repl.complete("x = ", 4).candidates.is_empty());
+    assert!(repl.complete("12", 2).candidates.is_empty());
+}
+
+#[test]
+fn test_line_buffer_completion() {
+    let repl = REPL::new();
+    let history = History::new();
+
+    let mut buffer = LineBuffer::new();
+    for c in "file_e".chars() {
+        buffer.apply(Key::Char(c), &history);
+    }
+    let hint = buffer.complete(&repl);
+    assert_eq!(buffer.text(), "file_exists");
+    assert!(hint.unwrap().starts_with("file_exists(path)"));
+
+    // Ambiguous words extend to the common prefix, then list the choices
+    let mut buffer = LineBuffer::new();
+    for c in "rea".chars() {
+        buffer.apply(Key::Char(c), &history);
+    }
+    assert_eq!(buffer.complete(&repl), None);
+    assert_eq!(buffer.text(), "read");
+
+    let mut buffer = LineBuffer::new();
+    for c in "re".chars() {
+        buffer.apply(Key::Char(c), &history);
+    }
+    let listing = buffer.complete(&repl).unwrap();
+    assert_eq!(listing, "read_file  readline  return  reverse");
+    assert_eq!(buffer.text(), "re");
+
+    assert_eq!(buffer.complete(&NoCompletion), None);
+}
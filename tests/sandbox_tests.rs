@@ -0,0 +1,200 @@
+use std::fs;
+use tempfile::TempDir;
+
+use jing::compiler::Compiler;
+use jing::error::{JingError, JingResult};
+use jing::lexer::Lexer;
+use jing::parser::Parser;
+use jing::sandbox::Capabilities;
+use jing::vm::VM;
+
+/// Helper function to run Jing code under the given capabilities
+fn run_sandboxed(code: &str, capabilities: Capabilities) -> JingResult<VM> {
+    let mut lexer = Lexer::new(code);
+    let tokens = lexer.tokenize()?;
+
+    let mut parser = Parser::new(tokens);
+    let statements = parser.parse()?;
+
+    let mut compiler = Compiler::new();
+    let chunk = compiler.compile(statements)?;
+
+    let mut vm = VM::new();
+    vm.set_capabilities(capabilities);
+    vm.interpret(chunk)?;
+
+    Ok(vm)
+}
+
+/// Convert a path to a string usable inside a Jing string literal
+fn jing_path(path: &std::path::Path) -> String {
+    path.to_str().expect("Invalid path").replace('\\', "/")
+}
+
+#[test]
+fn test_no_io_denies_filesystem() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let file = temp_dir.path().join("data.txt");
+    fs::write(&file, "secret").unwrap();
+    let path = jing_path(&file);
+
+    for code in [
+        format!(r#"read_file("{}");"#, path),
+        format!(r#"write_file("{}", "x");"#, path),
+        format!(r#"file_exists("{}");"#, path),
+    ] {
+        let result = run_sandboxed(&code, Capabilities::none());
+        assert!(
+            matches!(result, Err(JingError::PermissionDenied { .. })),
+            "Expected PermissionDenied for: {}",
+            code
+        );
+    }
+
+    assert_eq!(fs::read_to_string(&file).unwrap(), "secret");
+}
+
+#[test]
+fn test_read_only_allows_reads_only() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let file = temp_dir.path().join("data.txt");
+    fs::write(&file, "contents").unwrap();
+    let path = jing_path(&file);
+
+    let vm = run_sandboxed(
+        &format!(r#"let text = read_file("{}");"#, path),
+        Capabilities::read_only(),
+    )
+    .unwrap();
+    assert_eq!(
+        vm.get_global("text"),
+        Some(jing::Value::String("contents".to_string()))
+    );
+
+    let result = run_sandboxed(
+        &format!(r#"write_file("{}", "changed");"#, path),
+        Capabilities::read_only(),
+    );
+    assert!(matches!(result, Err(JingError::PermissionDenied { .. })));
+    assert_eq!(fs::read_to_string(&file).unwrap(), "contents");
+}
+
+#[test]
+fn test_allowed_directories() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let sandbox_dir = temp_dir.path().join("sandbox");
+    fs::create_dir(&sandbox_dir).unwrap();
+    let outside = temp_dir.path().join("outside.txt");
+    fs::write(&outside, "outside").unwrap();
+
+    let capabilities = Capabilities::directories([&sandbox_dir]);
+
+    // Writing and reading inside the directory works, even for new files
+    let inside = jing_path(&sandbox_dir.join("new.txt"));
+    let vm = run_sandboxed(
+        &format!(
+            r#"write_file("{}", "hello"); let text = read_file("{}");"#,
+            inside, inside
+        ),
+        capabilities.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        vm.get_global("text"),
+        Some(jing::Value::String("hello".to_string()))
+    );
+
+    // Direct access outside the directory is denied
+    let result = run_sandboxed(
+        &format!(r#"read_file("{}");"#, jing_path(&outside)),
+        capabilities.clone(),
+    );
+    assert!(matches!(result, Err(JingError::PermissionDenied { .. })));
+
+    // `..` components cannot escape the directory
+    let escape = format!("{}/../outside.txt", jing_path(&sandbox_dir));
+    let result = run_sandboxed(&format!(r#"read_file("{}");"#, escape), capabilities);
+    assert!(matches!(result, Err(JingError::PermissionDenied { .. })));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_dangling_symlink_cannot_escape() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let sandbox_dir = temp_dir.path().join("sandbox");
+    fs::create_dir(&sandbox_dir).unwrap();
+    let capabilities = Capabilities::directories([&sandbox_dir]);
+
+    // Writing through a link to a missing file outside would create it
+    let target = temp_dir.path().join("outside.txt");
+    let link = sandbox_dir.join("link.txt");
+    std::os::unix::fs::symlink(&target, &link).unwrap();
+    let result = run_sandboxed(
+        &format!(r#"write_file("{}", "escaped");"#, jing_path(&link)),
+        capabilities.clone(),
+    );
+    assert!(matches!(result, Err(JingError::PermissionDenied { .. })));
+    assert!(!target.exists());
+
+    // A dangling link to a new file inside the directory is fine
+    let inside = sandbox_dir.join("inside.txt");
+    let link = sandbox_dir.join("inside_link.txt");
+    std::os::unix::fs::symlink("inside.txt", &link).unwrap();
+    run_sandboxed(
+        &format!(r#"write_file("{}", "kept");"#, jing_path(&link)),
+        capabilities.clone(),
+    )
+    .unwrap();
+    assert_eq!(fs::read_to_string(&inside).unwrap(), "kept");
+
+    // Symlink loops are refused rather than followed forever
+    let looped = sandbox_dir.join("loop.txt");
+    std::os::unix::fs::symlink("loop.txt", &looped).unwrap();
+    let result = run_sandboxed(
+        &format!(r#"write_file("{}", "x");"#, jing_path(&looped)),
+        capabilities,
+    );
+    assert!(matches!(result, Err(JingError::PermissionDenied { .. })));
+}
+
+#[test]
+fn test_stdin_denied() {
+    let result = run_sandboxed("readline();", Capabilities::all().with_stdin(false));
+    assert!(matches!(result, Err(JingError::PermissionDenied { .. })));
+
+    let result = run_sandboxed(r#"input("> ");"#, Capabilities::none());
+    assert!(matches!(result, Err(JingError::PermissionDenied { .. })));
+}
+
+#[test]
+fn test_environment_denied() {
+    let result = run_sandboxed(r#"env("HOME");"#, Capabilities::read_only());
+    assert!(matches!(result, Err(JingError::PermissionDenied { .. })));
+
+    let result = run_sandboxed(
+        r#"set_env("JING_SANDBOX_TEST", "1");"#,
+        Capabilities::all().with_env(false),
+    );
+    assert!(matches!(result, Err(JingError::PermissionDenied { .. })));
+    assert!(std::env::var_os("JING_SANDBOX_TEST").is_none());
+
+    // Variables set by a script stay in its VM
+    let vm = run_sandboxed(
+        r#"set_env("JING_OVERLAY_TEST", "1"); let seen = env("JING_OVERLAY_TEST");"#,
+        Capabilities::all(),
+    )
+    .unwrap();
+    assert_eq!(
+        vm.get_global("seen"),
+        Some(jing::value::Value::String("1".to_string()))
+    );
+    assert_eq!(vm.env_var("JING_OVERLAY_TEST"), Some("1".to_string()));
+    assert!(std::env::var_os("JING_OVERLAY_TEST").is_none());
+    assert!(VM::new().env_var("JING_OVERLAY_TEST").is_none());
+
+    // Arguments and exit are not host access and stay available
+    let vm = run_sandboxed("let n = len(args());", Capabilities::none()).unwrap();
+    assert_eq!(vm.get_global("n"), Some(jing::value::Value::Number(0.0)));
+    let result = run_sandboxed("exit(2);", Capabilities::none());
+    assert!(matches!(result, Err(JingError::Exit { code: 2 })));
+}
@@ -0,0 +1,163 @@
+use std::fs;
+
+use jing::lexer::{Lexer, Span, TokenType};
+use jing::syntax::{self, NodeKind, SyntaxNode, TokenKind};
+
+/// Kinds of the nodes in the tree, in pre-order
+fn node_kinds(node: &SyntaxNode) -> Vec<NodeKind> {
+    let mut kinds = vec![node.kind];
+    for child in node.child_nodes() {
+        kinds.extend(node_kinds(child));
+    }
+    kinds
+}
+
+#[test]
+fn test_lossless_tokens_round_trip() {
+    let source = "#!/usr/bin/env jing\r\nlet  x = \"é\";\t// note\n\nprint(x);";
+    let tokens = Lexer::new(source).tokenize_lossless().unwrap();
+
+    let text: String = tokens.iter().map(|token| token.text.as_str()).collect();
+    assert_eq!(text, source);
+    for token in &tokens {
+        assert_eq!(&source[token.span.start..token.span.end], token.text);
+    }
+
+    // The `\r` of a CRLF line end is whitespace, except on the `#!` line
+    let kinds: Vec<_> = tokens.iter().take(7).map(|token| &token.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            &TokenKind::Shebang,
+            &TokenKind::Newline,
+            &TokenKind::Token(TokenType::Let),
+            &TokenKind::Whitespace,
+            &TokenKind::Token(TokenType::Identifier("x".to_string())),
+            &TokenKind::Whitespace,
+            &TokenKind::Token(TokenType::Equal),
+        ]
+    );
+    let comment = tokens
+        .iter()
+        .find(|token| token.kind == TokenKind::Comment)
+        .unwrap();
+    assert_eq!(comment.text, "// note");
+}
+
+#[test]
+fn test_tokens_have_byte_spans() {
+    let tokens = Lexer::new("s = \"é\" + 10;").tokenize().unwrap();
+    let spans: Vec<_> = tokens.iter().map(|token| token.span).collect();
+    // `é` is two bytes long
+    assert_eq!(
+        spans,
+        [
+            Span::new(0, 1),
+            Span::new(2, 3),
+            Span::new(4, 8),
+            Span::new(9, 10),
+            Span::new(11, 13),
+            Span::new(13, 14),
+            Span::new(14, 14),
+        ]
+    );
+}
+
+#[test]
+fn test_cst_round_trips_bundled_sources() {
+    for dir in ["examples", "std"] {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "jing") {
+                let source = fs::read_to_string(&path).unwrap();
+                let tree = syntax::parse(&source);
+                assert!(tree.error.is_none(), "{}", path.display());
+                assert_eq!(tree.root.text(), source, "{}", path.display());
+                assert_eq!(tree.root.span, Span::new(0, source.len()));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_cst_node_structure() {
+    let source = "fn f(a, b = 1) {\n    return (a + b) * -c.d(1, x: 2); // done\n}\n";
+    let tree = syntax::parse(source);
+
+    use NodeKind::*;
+    assert_eq!(
+        node_kinds(&tree.root),
+        [
+            Root, FnStmt, ParamList, Param, Param, Literal, Block, ReturnStmt, BinaryExpr,
+            ParenExpr, BinaryExpr, NameRef, NameRef, UnaryExpr, CallExpr, GetExpr, NameRef,
+            ArgList, Literal, NamedArg, Literal,
+        ]
+    );
+
+    // Trivia between statements belongs to the enclosing node
+    let block = tree.root.node_at(20).unwrap();
+    assert_eq!(block.kind, Block);
+    assert!(block.text().contains("// done"));
+
+    let offset = source.find("c.d").unwrap();
+    assert_eq!(tree.root.node_at(offset).unwrap().kind, NameRef);
+    assert_eq!(tree.root.token_at(offset).unwrap().text, "c");
+    assert_eq!(tree.root.token_at(offset + 2).unwrap().text, "d");
+
+    // A cursor right after the last token still finds it
+    let tree = syntax::parse("print(total");
+    assert_eq!(tree.root.token_at(11).unwrap().text, "total");
+}
+
+#[test]
+fn test_cst_keeps_source_after_parse_errors() {
+    let source = "let ok = 1;\nlet broken = ;\nprint(ok);\n";
+    let tree = syntax::parse(source);
+
+    assert!(tree.error.is_some());
+    assert_eq!(tree.root.text(), source);
+    let kinds: Vec<_> = tree.root.child_nodes().map(|node| node.kind).collect();
+    assert_eq!(
+        kinds,
+        [NodeKind::LetStmt, NodeKind::Error, NodeKind::ExprStmt]
+    );
+    // The error node starts at the failing statement and ends at its `;`
+    let error = tree.root.child_nodes().nth(1).unwrap();
+    assert_eq!(error.text(), "let broken = ;");
+
+    // An error inside a block only takes its statement, and a stray `}`
+    // is skipped
+    let source = "fn f() {\n  let = 1;\n  print(2);\n}\n}\nlet after = 3;\n";
+    let tree = syntax::parse(source);
+    assert_eq!(tree.root.text(), source);
+    let kinds: Vec<_> = tree.root.child_nodes().map(|node| node.kind).collect();
+    assert_eq!(
+        kinds,
+        [NodeKind::FnStmt, NodeKind::Error, NodeKind::LetStmt]
+    );
+    let block = tree
+        .root
+        .descendants()
+        .into_iter()
+        .find(|node| node.kind == NodeKind::Block)
+        .unwrap();
+    let kinds: Vec<_> = block.child_nodes().map(|node| node.kind).collect();
+    assert_eq!(kinds, [NodeKind::Error, NodeKind::ExprStmt]);
+
+    // Text the lexer cannot read becomes an error token
+    let source = "let x = 1 $ 2;\nfn f() {}\nlet s = \"open";
+    let tree = syntax::parse(source);
+    assert!(tree.error.is_some());
+    assert_eq!(tree.root.text(), source);
+    let errors: Vec<_> = tree
+        .root
+        .tokens()
+        .filter(|token| token.kind == TokenKind::Error)
+        .map(|token| token.text.as_str())
+        .collect();
+    assert_eq!(errors, ["$", "\"open"]);
+    assert!(tree
+        .root
+        .child_nodes()
+        .any(|node| node.kind == NodeKind::FnStmt));
+}
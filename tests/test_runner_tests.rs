@@ -0,0 +1,130 @@
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+use jing::compiler::Compiler;
+use jing::error::{JingError, JingResult};
+use jing::lexer::Lexer;
+use jing::parser::Parser;
+use jing::testing;
+use jing::vm::VM;
+
+/// Helper function to run Jing code in a fresh VM
+fn run_code(code: &str) -> JingResult<VM> {
+    let tokens = Lexer::new(code).tokenize()?;
+    let statements = Parser::new(tokens).parse()?;
+    let chunk = Compiler::new().compile(statements)?;
+
+    let mut vm = VM::new();
+    vm.interpret(chunk)?;
+    Ok(vm)
+}
+
+/// The message of a failed assertion, panicking on any other result
+fn assertion_message(code: &str) -> String {
+    match run_code(code) {
+        Err(JingError::AssertionFailed { message }) => message,
+        other => panic!("expected an assertion failure, got {:?}", other.err()),
+    }
+}
+
+fn write_file(dir: &Path, name: &str, source: &str) {
+    let path = dir.join(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, source).unwrap();
+}
+
+#[test]
+fn test_assert_builtins_pass() {
+    run_code(
+        r#"
+        assert(true);
+        assert(1 < 2, "ordering");
+        assert_eq(range(3), range(3));
+        assert_eq("a" + "b", "ab", "concatenation");
+        assert_ne(1, 2);
+        "#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_assert_failures_describe_values() {
+    assert_eq!(assertion_message("assert(false, \"broken\");"), "broken");
+    assert_eq!(
+        assertion_message("assert(nil);"),
+        "expected a truthy value, got nil"
+    );
+
+    let message = assertion_message("assert_eq(\"a\", \"b\");");
+    assert!(message.contains("left: \"a\""));
+    assert!(message.contains("right: \"b\""));
+}
+
+#[test]
+fn test_assert_eq_diffs_collections() {
+    let message = assertion_message(
+        "fn double(x) { return x * 2; } assert_eq(range(3), map(range(3), double));",
+    );
+    assert!(message.contains("diff: at [1]: 1 != 2"), "{}", message);
+
+    let message = assertion_message("assert_eq(range(2), range(3));");
+    assert!(message.contains("lengths differ: 2 vs 3"), "{}", message);
+
+    let message = assertion_message("assert_ne(2, 2, \"same\");");
+    assert!(message.starts_with("same"));
+}
+
+#[test]
+fn test_discover_and_run_files() {
+    let dir = TempDir::new().unwrap();
+    write_file(
+        dir.path(),
+        "math_test.jing",
+        r#"
+        let runs = 0;
+        fn test_first() { runs = runs + 1; assert_eq(runs, 1); }
+        fn test_second() { runs = runs + 1; assert_eq(runs, 1); }
+        fn test_failing() { assert_eq(1, 2); }
+        fn helper() { assert(false); }
+        "#,
+    );
+    write_file(dir.path(), "nested/more_test.jing", "fn test_ok() {}");
+    write_file(dir.path(), "nested/script.jing", "fn test_ignored() {}");
+    write_file(dir.path(), ".hidden/skip_test.jing", "fn test_hidden() {}");
+
+    let root = dir.path().to_string_lossy().into_owned();
+    let files = testing::discover(&[root]).unwrap();
+    let names: Vec<_> = files
+        .iter()
+        .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(names, ["math_test.jing", "more_test.jing"]);
+
+    // Every test gets a fresh VM, so both see `runs` start at 0
+    let report = testing::run_file(&files[0], None);
+    assert!(report.error.is_none());
+    let outcomes: Vec<_> = report
+        .results
+        .iter()
+        .map(|result| (result.name.as_str(), result.passed()))
+        .collect();
+    assert_eq!(
+        outcomes,
+        [
+            ("test_first", true),
+            ("test_second", true),
+            ("test_failing", false)
+        ]
+    );
+    assert!(matches!(
+        report.results[2].outcome,
+        Err(JingError::AssertionFailed { .. })
+    ));
+
+    let report = testing::run_file(&files[0], Some("second"));
+    assert_eq!(report.results.len(), 1);
+    assert_eq!(report.filtered, 2);
+
+    assert!(testing::discover(&["/no/such/dir".to_string()]).is_err());
+}
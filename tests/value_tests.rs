@@ -1,3 +1,4 @@
+use jing::features::Arity;
 use jing::value::{Environment, Value};
 
 #[test]
@@ -11,8 +12,9 @@ fn test_value_display() {
 
     let func = Value::Function {
         name: "test".to_string(),
-        arity: 2,
+        arity: Arity::exact(2),
         chunk_start: 0,
+        module: 0,
     };
     assert_eq!(format!("{}", func), "<fn test(2 args)>");
 }
@@ -37,8 +39,9 @@ fn test_value_truthiness() {
 
     let func = Value::Function {
         name: "test".to_string(),
-        arity: 0,
+        arity: Arity::exact(0),
         chunk_start: 0,
+        module: 0,
     };
     assert!(func.is_truthy());
 }
@@ -52,8 +55,9 @@ fn test_value_type_names() {
 
     let func = Value::Function {
         name: "test".to_string(),
-        arity: 0,
+        arity: Arity::exact(0),
         chunk_start: 0,
+        module: 0,
     };
     assert_eq!(func.type_name(), "function");
 }
@@ -289,3 +293,23 @@ fn test_environment_edge_cases() {
     env.define("test".to_string(), Value::Bool(true));
     assert_eq!(env.get("test").unwrap(), Value::Bool(true));
 }
+
+#[test]
+fn test_collection_values() {
+    let list = Value::List(vec![
+        Value::Number(1.0),
+        Value::String("two".to_string()),
+        Value::Nil,
+    ]);
+    assert_eq!(format!("{}", list), r#"[1, "two", nil]"#);
+    assert_eq!(list.type_name(), "list");
+    assert!(list.equals(&list.clone()));
+
+    let mut entries = std::collections::BTreeMap::new();
+    entries.insert("b".to_string(), Value::Bool(true));
+    entries.insert("a".to_string(), Value::Number(1.0));
+    let map = Value::Map(entries);
+    assert_eq!(format!("{}", map), r#"{"a": 1, "b": true}"#);
+    assert_eq!(map.type_name(), "map");
+    assert!(!map.equals(&list));
+}